@@ -0,0 +1,239 @@
+use crate::ReduceError;
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+/// The outcome of an `Action` dispatched via
+/// [Store::dispatch_async()](crate::Store::dispatch_async()).
+#[derive(Debug)]
+pub enum DispatchOutcome<State, Event> {
+    /// The action has been reduced, its effects have been processed
+    /// and the store listeners have been notified.
+    Completed {
+        /// The state of the store immediately after the action was
+        /// processed.
+        state: Rc<State>,
+        /// The events that were sent to the store listeners (after
+        /// being modified by
+        /// [Middleware::on_notify()](crate::middleware::Middleware::on_notify())).
+        events: Vec<Event>,
+    },
+    /// The action was rejected by the store's
+    /// [Reducer::try_reduce()](crate::Reducer::try_reduce()), see
+    /// [Store::new_fallible()](crate::Store::new_fallible()). The
+    /// state was left untouched, and the store's error listeners have
+    /// also been notified.
+    Rejected(ReduceError),
+    /// The action was discarded before it could be processed, for
+    /// example because the store was dropped.
+    Cancelled,
+}
+
+/// A description of what a
+/// [Store::dispatch()](crate::Store::dispatch()) did, intended for
+/// making assertions in application tests.
+#[derive(Debug)]
+pub struct DispatchReport<Event, Effect> {
+    /// `true` if the action was queued instead of being processed,
+    /// because it was dispatched while another dispatch was in
+    /// progress (for example from within a listener callback or
+    /// middleware), or within a
+    /// [Store::batch()](crate::Store::batch()). If so, the other
+    /// fields are empty.
+    pub queued: bool,
+    /// The events which were sent to the store listeners for the
+    /// action (after being modified by
+    /// [Middleware::on_notify()](crate::middleware::Middleware::on_notify())).
+    pub events: Vec<Event>,
+    /// The number of effects which were produced by the action.
+    pub effects_emitted: usize,
+    /// The effects produced by the action which were not consumed by
+    /// any middleware's
+    /// [Middleware::process_effect()](crate::middleware::Middleware::process_effect()).
+    pub unconsumed_effects: Vec<Effect>,
+    /// `true` if the store's state is a different [Rc] after the
+    /// dispatch than before it.
+    pub state_changed: bool,
+    /// The number of actions which were processed during the
+    /// dispatch, including the dispatched action and any actions
+    /// that were queued while it was being processed (for example by
+    /// effects).
+    pub actions_processed: usize,
+    /// The error returned by the store's
+    /// [Reducer::try_reduce()](crate::Reducer::try_reduce()) if it
+    /// rejected the action, see
+    /// [Store::new_fallible()](crate::Store::new_fallible()).
+    pub error: Option<ReduceError>,
+}
+
+impl<Event, Effect> DispatchReport<Event, Effect> {
+    /// The report for an action which was queued.
+    pub(crate) fn queued() -> Self {
+        Self {
+            queued: true,
+            events: Vec::new(),
+            effects_emitted: 0,
+            unconsumed_effects: Vec::new(),
+            state_changed: false,
+            actions_processed: 0,
+            error: None,
+        }
+    }
+}
+
+/// State shared between a [DispatchFuture] and its
+/// [DispatchCompleter].
+struct DispatchShared<State, Event> {
+    outcome: Option<DispatchOutcome<State, Event>>,
+    waker: Option<Waker>,
+}
+
+/// The sending half of a [DispatchFuture], which travels with the
+/// `Action` through the [Store](crate::Store)'s dispatch queue. If it
+/// is dropped without being completed, the future resolves to
+/// [DispatchOutcome::Cancelled].
+pub(crate) struct DispatchCompleter<State, Event> {
+    shared: Rc<RefCell<DispatchShared<State, Event>>>,
+}
+
+impl<State, Event> DispatchCompleter<State, Event> {
+    fn resolve(&self, outcome: DispatchOutcome<State, Event>) {
+        let waker = {
+            let mut shared = self.shared.borrow_mut();
+            if shared.outcome.is_some() {
+                return;
+            }
+            shared.outcome = Some(outcome);
+            shared.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Resolve the associated [DispatchFuture] with the result of
+    /// processing the action.
+    pub fn complete(self, state: Rc<State>, events: Vec<Event>) {
+        self.resolve(DispatchOutcome::Completed { state, events });
+    }
+
+    /// Resolve the associated [DispatchFuture] with the `error` which
+    /// the reducer rejected the action with.
+    pub fn reject(self, error: ReduceError) {
+        self.resolve(DispatchOutcome::Rejected(error));
+    }
+}
+
+impl<State, Event> Drop for DispatchCompleter<State, Event> {
+    fn drop(&mut self) {
+        self.resolve(DispatchOutcome::Cancelled);
+    }
+}
+
+/// A future returned by
+/// [Store::dispatch_async()](crate::Store::dispatch_async()), which
+/// resolves once the dispatched `Action` has been fully processed by
+/// the store.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct DispatchFuture<State, Event> {
+    shared: Rc<RefCell<DispatchShared<State, Event>>>,
+}
+
+impl<State, Event> DispatchFuture<State, Event> {
+    /// Create a new future, and the completer used to resolve it.
+    pub(crate) fn new() -> (Self, DispatchCompleter<State, Event>) {
+        let shared = Rc::new(RefCell::new(DispatchShared {
+            outcome: None,
+            waker: None,
+        }));
+
+        (
+            Self {
+                shared: shared.clone(),
+            },
+            DispatchCompleter { shared },
+        )
+    }
+
+    /// Returns `true` if the `Action` associated with this future has
+    /// finished being processed (or has been cancelled).
+    pub fn is_complete(&self) -> bool {
+        self.shared.borrow().outcome.is_some()
+    }
+}
+
+impl<State, Event> Future for DispatchFuture<State, Event> {
+    type Output = DispatchOutcome<State, Event>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.borrow_mut();
+        match shared.outcome.take() {
+            Some(outcome) => Poll::Ready(outcome),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DispatchFuture, DispatchOutcome};
+    use std::{
+        future::Future,
+        pin::Pin,
+        rc::Rc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_complete_wakes() {
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let (mut future, completer) = DispatchFuture::<i32, ()>::new();
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+        completer.complete(Rc::new(5), vec![()]);
+        assert_eq!(1, counter.0.load(Ordering::SeqCst));
+
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(DispatchOutcome::Completed { state, events }) => {
+                assert_eq!(5, *state);
+                assert_eq!(vec![()], events);
+            }
+            _ => panic!("expected the future to be completed"),
+        }
+    }
+
+    #[test]
+    fn test_dropped_completer_cancels() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let (mut future, completer) = DispatchFuture::<i32, ()>::new();
+        drop(completer);
+
+        assert!(matches!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready(DispatchOutcome::Cancelled)
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+//! Executors used to run asynchronous `Effect` handlers, see
+//! [AsyncEffectMiddleware](crate::middleware::async_effect::AsyncEffectMiddleware).
+//!
+//! ## Optional Features
+//!
+//! The following implementations of [EffectExecutor] are available
+//! via crate features:
+//!
+//! + `"futures"` - [futures::executor::LocalSpawner], the spawner
+//!   for a [futures::executor::LocalPool].
+//! + `"tokio"` - [TokioLocalExecutor], which spawns onto the current
+//!   [tokio::task::LocalSet].
+//! + `"wasm-bindgen-futures"` - [WasmBindgenExecutor], which spawns
+//!   onto the browser's event loop using
+//!   [wasm_bindgen_futures::spawn_local()].
+
+use std::{future::Future, pin::Pin, rc::Rc};
+
+/// A boxed future which is not required to be `Send`, as produced by
+/// asynchronous `Effect` handlers.
+pub type LocalBoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+
+/// An executor which is capable of running futures which are not
+/// `Send` on the current thread, used to run the futures produced by
+/// asynchronous `Effect` handlers.
+pub trait EffectExecutor {
+    /// Spawn the `future` onto this executor, to be run to
+    /// completion in the background.
+    fn spawn_local(&self, future: LocalBoxFuture<()>);
+}
+
+impl<E> EffectExecutor for Rc<E>
+where
+    E: EffectExecutor + ?Sized,
+{
+    fn spawn_local(&self, future: LocalBoxFuture<()>) {
+        (**self).spawn_local(future)
+    }
+}
+
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+impl EffectExecutor for futures::executor::LocalSpawner {
+    fn spawn_local(&self, future: LocalBoxFuture<()>) {
+        futures::task::LocalSpawnExt::spawn_local(self, future)
+            .expect("unable to spawn effect, the LocalPool has been dropped");
+    }
+}
+
+/// An [EffectExecutor] which spawns futures onto the current
+/// [tokio::task::LocalSet] using [tokio::task::spawn_local()].
+///
+/// **Note:** the store's [Store::dispatch()](crate::Store::dispatch())
+/// needs to be called from within the context of a
+/// [tokio::task::LocalSet], otherwise spawning will panic.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Default, Clone, Copy, Debug)]
+pub struct TokioLocalExecutor;
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+impl EffectExecutor for TokioLocalExecutor {
+    fn spawn_local(&self, future: LocalBoxFuture<()>) {
+        tokio::task::spawn_local(future);
+    }
+}
+
+/// An [EffectExecutor] for applications running in the browser,
+/// which spawns futures onto the javascript event loop using
+/// [wasm_bindgen_futures::spawn_local()].
+#[cfg(feature = "wasm-bindgen-futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm-bindgen-futures")))]
+#[derive(Default, Clone, Copy, Debug)]
+pub struct WasmBindgenExecutor;
+
+#[cfg(feature = "wasm-bindgen-futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm-bindgen-futures")))]
+impl EffectExecutor for WasmBindgenExecutor {
+    fn spawn_local(&self, future: LocalBoxFuture<()>) {
+        wasm_bindgen_futures::spawn_local(future);
+    }
+}
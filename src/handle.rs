@@ -0,0 +1,213 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, Weak},
+};
+
+/// Callback used to wake the thread which owns a
+/// [Store](crate::Store) when an action has been posted to it via a
+/// [StoreHandle].
+type WakeFn = Arc<dyn Fn() + Send + Sync>;
+
+/// A queue of actions posted from other threads via a [StoreHandle],
+/// owned by a [Store](crate::Store) and drained by
+/// [Store::drain_remote()](crate::Store::drain_remote()).
+pub(crate) struct RemoteQueue<Action> {
+    actions: Mutex<VecDeque<Action>>,
+    wake: Mutex<Option<WakeFn>>,
+}
+
+/// Lock the `mutex`, ignoring poisoning. The locks in the
+/// [RemoteQueue] are never held while user code is running.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<Action> RemoteQueue<Action> {
+    pub fn new() -> Self {
+        Self {
+            actions: Mutex::new(VecDeque::new()),
+            wake: Mutex::new(None),
+        }
+    }
+
+    pub fn push(&self, action: Action) {
+        lock(&self.actions).push_back(action);
+
+        let wake = lock(&self.wake).clone();
+        if let Some(wake) = wake {
+            wake();
+        }
+    }
+
+    /// Remove all the actions which are currently in the queue.
+    pub fn take(&self) -> VecDeque<Action> {
+        std::mem::take(&mut *lock(&self.actions))
+    }
+
+    pub fn set_wake(&self, wake: Option<WakeFn>) {
+        *lock(&self.wake) = wake;
+    }
+}
+
+/// A `Send + Clone` handle to a [Store](crate::Store), obtained via
+/// [Store::handle()](crate::Store::handle()), which can be used by
+/// other threads to post actions to the store.
+///
+/// [Store](crate::Store) is not thread-safe, so actions posted via
+/// this handle are not processed immediately. They are held in a
+/// queue until the thread which owns the store calls
+/// [Store::drain_remote()](crate::Store::drain_remote()). A wake
+/// callback can be registered with
+/// [Store::set_remote_wake()](crate::Store::set_remote_wake()) to let
+/// the owning thread know that it needs to do so (for example by
+/// sending a message to its event loop).
+///
+/// If a thread-safe store is required instead, see
+/// [sync::Store](crate::sync::Store).
+///
+/// ## Example
+///
+/// ```
+/// # use reactive_state::{ReducerFn, ReducerResult, Store};
+/// # use std::rc::Rc;
+/// let reducer: ReducerFn<u32, u32, (), ()> = |state, action| ReducerResult {
+///     state: Rc::new(**state + *action),
+///     events: vec![()],
+///     effects: vec![],
+/// };
+/// let store = Store::new(reducer, 0);
+///
+/// let handle = store.handle();
+/// std::thread::spawn(move || handle.dispatch(5u32))
+///     .join()
+///     .unwrap();
+///
+/// // The action has been posted, but not yet processed.
+/// assert_eq!(0, *store.state());
+///
+/// assert_eq!(1, store.drain_remote());
+/// assert_eq!(5, *store.state());
+/// ```
+pub struct StoreHandle<Action> {
+    queue: Weak<RemoteQueue<Action>>,
+}
+
+impl<Action> StoreHandle<Action> {
+    pub(crate) fn new(queue: &Arc<RemoteQueue<Action>>) -> Self {
+        Self {
+            queue: Arc::downgrade(queue),
+        }
+    }
+
+    /// Post an `Action` to the [Store](crate::Store), to be
+    /// dispatched on the thread which owns it during the next
+    /// [Store::drain_remote()](crate::Store::drain_remote()).
+    ///
+    /// If the store has already been dropped, the action is
+    /// discarded.
+    pub fn dispatch<A: Into<Action>>(&self, action: A) {
+        if let Some(queue) = self.queue.upgrade() {
+            queue.push(action.into());
+        }
+    }
+
+    /// Returns `true` if the [Store](crate::Store) which this handle
+    /// posts actions to has not yet been dropped.
+    pub fn is_alive(&self) -> bool {
+        self.queue.strong_count() > 0
+    }
+}
+
+impl<Action> Clone for StoreHandle<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ReducerFn, ReducerResult, Store, StoreHandle};
+    use std::{
+        rc::Rc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    fn test_store() -> Store<i32, i32, (), ()> {
+        let reducer: ReducerFn<i32, i32, (), ()> = |state, action| ReducerResult {
+            state: Rc::new(**state + *action),
+            events: vec![()],
+            effects: vec![],
+        };
+
+        Store::new(reducer, 0)
+    }
+
+    #[test]
+    fn test_handle_send_clone() {
+        fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+        assert_send_sync_clone::<StoreHandle<i32>>();
+    }
+
+    #[test]
+    fn test_drain_remote() {
+        let store = test_store();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = store.handle();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        handle.dispatch(1);
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(0, *store.state());
+        assert_eq!(40, store.drain_remote());
+        assert_eq!(40, *store.state());
+        assert_eq!(0, store.drain_remote());
+    }
+
+    #[test]
+    fn test_remote_wake() {
+        let store = test_store();
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let wakes_local = wakes.clone();
+        store.set_remote_wake(move || {
+            wakes_local.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let handle = store.handle();
+        std::thread::spawn(move || {
+            handle.dispatch(2);
+            handle.dispatch(3);
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(2, wakes.load(Ordering::SeqCst));
+        store.drain_remote();
+        assert_eq!(5, *store.state());
+    }
+
+    #[test]
+    fn test_handle_store_dropped() {
+        let store = test_store();
+        let handle = store.handle();
+        assert!(handle.is_alive());
+
+        drop(store);
+        assert!(!handle.is_alive());
+        handle.dispatch(1);
+    }
+}
@@ -73,8 +73,9 @@
 //!     *(callback_invokes_local.borrow_mut()) += 1;
 //! });
 //!
-//! // Subscribe to state changes which produce the IsOne event.
-//! store.subscribe_event(&callback, MyEvent::IsOne);
+//! // Subscribe to state changes which produce the IsOne event. The
+//! // listener remains subscribed until the subscription is dropped.
+//! let subscription = store.subscribe_event(&callback, MyEvent::IsOne);
 //!
 //! assert_eq!(0, store.state().variable);
 //! assert_eq!(0, *RefCell::borrow(&callback_invokes));
@@ -109,6 +110,11 @@
 //! // The callback was dropped before the action was dispatched,
 //! // and so it was not invoked.
 //! assert_eq!(1, *RefCell::borrow(&callback_invokes));
+//!
+//! // The listener has been removed from the store. Dropping the
+//! // subscription would also have removed it immediately.
+//! assert_eq!(0, store.listener_count());
+//! drop(subscription);
 //! ```
 //!
 //! ## Side Effects
@@ -119,6 +125,23 @@
 //! which are produced in the reducer are given to the store via the
 //! [ReducerResult](ReducerResult) that it returns. Side effects are
 //! designed to be executed/handled by store [middleware](middleware).
+//! Effects which need to be processed asynchronously can be handled
+//! by the
+//! [AsyncEffectMiddleware](middleware::async_effect::AsyncEffectMiddleware),
+//! using an [EffectExecutor](executor::EffectExecutor).
+//!
+//! ## Thread Safety
+//!
+//! [Store](Store) is built on [Rc](std::rc::Rc) and
+//! [RefCell](std::cell::RefCell), and can only be used from a single
+//! thread. A thread-safe equivalent (with the same reducer,
+//! middleware and listener model) which can be shared between threads
+//! is available in the [sync](crate::sync) module.
+//!
+//! Alternatively, other threads can post actions to a [Store](Store)
+//! using a [StoreHandle](StoreHandle), and the thread which owns the
+//! store dispatches them with
+//! [Store::drain_remote()](Store::drain_remote()).
 //!
 //! ## Optional Features
 //!
@@ -133,18 +156,47 @@
 //!   [wasm-bindgen](https://crates.io/crates/wasm-bindgen).
 //! + `"yew"` - Support for compatibility trait implementations on
 //!   [yew](https://crates.io/crates/yew) types.
+//! + `"persist"` - Middleware in the
+//!   [persist](crate::middleware::persist) module which persists the
+//!   store state using [serde](https://crates.io/crates/serde), and
+//!   rehydrates it when the application starts.
+//! + `"devtools"` - Middleware in the
+//!   [devtools](crate::middleware::devtools) module which connects
+//!   the store to the [Redux
+//!   DevTools](https://github.com/reduxjs/redux-devtools) using the
+//!   remote (remotedev) protocol over a WebSocket.
+//! + `"futures"`, `"tokio"`, `"wasm-bindgen-futures"` -
+//!   [EffectExecutor](executor::EffectExecutor) implementations for
+//!   the respective async runtimes.
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod dispatch;
+pub mod executor;
+mod handle;
 mod listener;
 pub mod middleware;
+mod panic;
 mod reducer;
+mod scope;
+mod selector;
 mod store;
+mod subscription;
+pub mod sync;
+mod transaction;
+pub mod undo;
 
 #[cfg(feature = "yew")]
 #[cfg_attr(docsrs, doc(cfg(feature = "yew")))]
 pub mod provider;
 
+pub use dispatch::{DispatchFuture, DispatchOutcome, DispatchReport};
+pub use handle::StoreHandle;
 pub use listener::*;
+pub use panic::{PanicSource, StorePanic};
 pub use reducer::*;
-pub use store::{Store, StoreRef};
+pub use scope::ScopedStore;
+pub use selector::Selector;
+pub use store::{Store, StoreRef, WeakStoreRef};
+pub use subscription::{NotifyPolicy, Subscription, SubscriptionId};
+pub use transaction::{Transaction, TransactionError};
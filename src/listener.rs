@@ -44,7 +44,9 @@ impl<State, Event> AsListener<State, Event> for Listener<State, Event> {
 /// [AsListener](AsListener) trait creates a weak reference to this
 /// callback in a [Listener](Listener), which is given to the
 /// [Store](crate::Store). When the callback is dropped, the listener will be
-/// removed from the store.
+/// removed from the store. The listener is also removed from the
+/// store when the returned [Subscription](crate::Subscription) is
+/// dropped.
 ///
 /// ```
 /// # use reactive_state::{ReducerFn, Store, ReducerResult};
@@ -56,7 +58,7 @@ impl<State, Event> AsListener<State, Event> for Listener<State, Event> {
 ///     println!("Callback invoked");
 /// });
 ///
-/// store.subscribe(&callback);
+/// let subscription = store.subscribe(&callback);
 /// ```
 ///
 /// ## Optional Features
@@ -95,6 +97,86 @@ where
     }
 }
 
+/// The function wrapped by a [BatchCallback].
+type BatchCallbackFn<State, Event> = dyn Fn(Rc<State>, &[Event]);
+
+/// A trait to take a [BatchCallback] or other custom callback type and
+/// produce a [BatchListener], a weak reference to that callback.
+pub trait AsBatchListener<State, Event> {
+    /// Produce a [BatchListener], a weak reference to this callback.
+    fn as_batch_listener(&self) -> BatchListener<State, Event>;
+}
+
+/// A weak reference to a callback function (usually
+/// [BatchCallback]) which is notified once per
+/// [Store::dispatch()](crate::Store::dispatch()) of changes to
+/// [Store](crate::Store) `State`, along with all the `Event`s
+/// produced by the dispatch that it is subscribed to.
+#[derive(Clone)]
+pub struct BatchListener<State, Event>(Weak<BatchCallbackFn<State, Event>>);
+
+impl<State, Event> BatchListener<State, Event> {
+    /// Attempt to upgrade the weak reference in this listener to a
+    /// [BatchCallback], otherwise if unable to, returns `None`.
+    pub fn as_callback(&self) -> Option<BatchCallback<State, Event>> {
+        self.0.upgrade().map(BatchCallback)
+    }
+}
+
+impl<State, Event> AsBatchListener<State, Event> for BatchListener<State, Event> {
+    fn as_batch_listener(&self) -> BatchListener<State, Event> {
+        BatchListener(self.0.clone())
+    }
+}
+
+/// A wrapper for a callback which is notified once per
+/// [Store::dispatch()](crate::Store::dispatch()) of changes to
+/// [Store](crate::Store) `State`, along with all the `Event`s
+/// produced by the dispatch that it is subscribed to. Unlike
+/// [Callback], which is invoked once for each matching event, this
+/// is invoked with all of them at once.
+///
+/// ## Example
+///
+/// ```
+/// # use reactive_state::{ReducerFn, Store, ReducerResult};
+/// # let reducer: ReducerFn<(), (), u32, ()> = |_state, _action| { ReducerResult::default() };
+/// # let store = Store::new(reducer, ());
+/// use reactive_state::BatchCallback;
+///
+/// let callback = BatchCallback::new(|_state, events: &[u32]| {
+///     println!("{} events", events.len());
+/// });
+///
+/// let subscription = store.subscribe_batch(&callback, vec![1, 2, 3]);
+/// ```
+#[derive(Clone)]
+pub struct BatchCallback<State, Event>(Rc<BatchCallbackFn<State, Event>>);
+
+impl<State, Event> AsBatchListener<State, Event> for &BatchCallback<State, Event> {
+    fn as_batch_listener(&self) -> BatchListener<State, Event> {
+        BatchListener(Rc::downgrade(&self.0))
+    }
+}
+
+impl<State, Event> BatchCallback<State, Event> {
+    pub fn new<C: Fn(Rc<State>, &[Event]) + 'static>(closure: C) -> Self {
+        BatchCallback(Rc::new(closure))
+    }
+    pub fn emit(&self, state: Rc<State>, events: &[Event]) {
+        (self.0)(state, events)
+    }
+}
+
+impl<C, State, Event> From<C> for BatchCallback<State, Event>
+where
+    C: Fn(Rc<State>, &[Event]) + 'static,
+{
+    fn from(closure: C) -> Self {
+        BatchCallback(Rc::new(closure))
+    }
+}
+
 #[cfg(feature = "yew")]
 #[cfg_attr(docsrs, doc(cfg(feature = "yew")))]
 impl<State, Event> From<yew::Callback<Rc<State>>> for Callback<State, Event>
@@ -0,0 +1,266 @@
+//! [Middleware] which processes `Effect`s asynchronously, by
+//! spawning the futures returned by an effect handler onto an
+//! [EffectExecutor], and dispatching the resulting actions back into
+//! the [Store](crate::Store) when they complete.
+//!
+//! ## Example
+//!
+//! ```
+//! # use reactive_state::{ReducerFn, ReducerResult, StoreRef};
+//! # use std::rc::Rc;
+//! use futures::{
+//!     executor::{LocalPool, LocalSpawner},
+//!     task::LocalSpawnExt,
+//! };
+//! use reactive_state::{
+//!     executor::{EffectExecutor, LocalBoxFuture},
+//!     middleware::async_effect::AsyncEffectMiddleware,
+//! };
+//!
+//! // An executor implementation is also available via the "futures"
+//! // crate feature.
+//! struct Executor(LocalSpawner);
+//!
+//! impl EffectExecutor for Executor {
+//!     fn spawn_local(&self, future: LocalBoxFuture<()>) {
+//!         LocalSpawnExt::spawn_local(&self.0, future).unwrap();
+//!     }
+//! }
+//!
+//! enum Action {
+//!     Fetch,
+//!     Loaded(u32),
+//! }
+//!
+//! enum Effect {
+//!     Fetch,
+//! }
+//!
+//! let reducer: ReducerFn<u32, Action, (), Effect> = |state, action| match action {
+//!     Action::Fetch => ReducerResult {
+//!         state: state.clone(),
+//!         events: vec![],
+//!         effects: vec![Effect::Fetch],
+//!     },
+//!     Action::Loaded(value) => ReducerResult {
+//!         state: Rc::new(*value),
+//!         events: vec![()],
+//!         effects: vec![],
+//!     },
+//! };
+//!
+//! let store = StoreRef::new(reducer, 0);
+//! let mut pool = LocalPool::new();
+//!
+//! store.add_middleware(AsyncEffectMiddleware::new(
+//!     &store,
+//!     Executor(pool.spawner()),
+//!     |effect| match effect {
+//!         Effect::Fetch => Ok(Box::pin(async { vec![Action::Loaded(42)] })),
+//!     },
+//! ));
+//!
+//! store.dispatch(Action::Fetch);
+//! assert_eq!(0, *store.state());
+//!
+//! pool.run_until_stalled();
+//! assert_eq!(42, *store.state());
+//! ```
+
+use super::Middleware;
+use crate::{
+    executor::{EffectExecutor, LocalBoxFuture},
+    Store, StoreRef, WeakStoreRef,
+};
+
+/// A function which handles an `Effect` by producing a future that
+/// resolves to the actions to dispatch back into the store, or
+/// returns `Err(effect)` if it does not handle the effect, so that
+/// it can be passed on to subsequent middleware.
+type EffectHandlerFn<Action, Effect> =
+    dyn Fn(Effect) -> Result<LocalBoxFuture<Vec<Action>>, Effect>;
+
+/// [Middleware] which processes `Effect`s asynchronously using an
+/// [EffectExecutor].
+///
+/// See [async_effect](super::async_effect) for more details.
+pub struct AsyncEffectMiddleware<State, Action, Event, Effect, X> {
+    store: WeakStoreRef<State, Action, Event, Effect>,
+    executor: X,
+    handler: Box<EffectHandlerFn<Action, Effect>>,
+}
+
+impl<State, Action, Event, Effect, X> AsyncEffectMiddleware<State, Action, Event, Effect, X>
+where
+    X: EffectExecutor,
+{
+    /// Create a new [AsyncEffectMiddleware] for the specified
+    /// `store`, which spawns the futures produced by `handler` onto
+    /// the `executor`.
+    ///
+    /// The `handler` returns `Ok(future)` if it handles the `Effect`,
+    /// where the `future` resolves to the actions which will be
+    /// dispatched to the `store` once it completes. Otherwise it
+    /// returns `Err(effect)`, and the effect is passed on to the next
+    /// middleware.
+    pub fn new<H>(store: &StoreRef<State, Action, Event, Effect>, executor: X, handler: H) -> Self
+    where
+        H: Fn(Effect) -> Result<LocalBoxFuture<Vec<Action>>, Effect> + 'static,
+    {
+        Self {
+            store: store.downgrade(),
+            executor,
+            handler: Box::new(handler),
+        }
+    }
+}
+
+impl<State, Action, Event, Effect, X> Middleware<State, Action, Event, Effect>
+    for AsyncEffectMiddleware<State, Action, Event, Effect, X>
+where
+    State: 'static,
+    Action: 'static,
+    Event: Clone + 'static,
+    Effect: 'static,
+    X: EffectExecutor,
+{
+    fn process_effect(
+        &self,
+        _store: &Store<State, Action, Event, Effect>,
+        effect: Effect,
+    ) -> Option<Effect> {
+        match (self.handler)(effect) {
+            Ok(future) => {
+                let store = self.store.clone();
+                self.executor.spawn_local(Box::pin(async move {
+                    let actions = future.await;
+                    if let Some(store) = store.upgrade() {
+                        for action in actions {
+                            store.dispatch(action);
+                        }
+                    }
+                }));
+                None
+            }
+            Err(effect) => Some(effect),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncEffectMiddleware;
+    use crate::{
+        executor::{EffectExecutor, LocalBoxFuture},
+        middleware::Middleware,
+        ReducerFn, ReducerResult, Store, StoreRef,
+    };
+    use futures::{
+        channel::oneshot,
+        executor::{LocalPool, LocalSpawner},
+        task::LocalSpawnExt,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    struct TestExecutor(LocalSpawner);
+
+    impl EffectExecutor for TestExecutor {
+        fn spawn_local(&self, future: LocalBoxFuture<()>) {
+            LocalSpawnExt::spawn_local(&self.0, future).unwrap();
+        }
+    }
+
+    enum TestAction {
+        Request,
+        Add(i32),
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum TestEffect {
+        Request,
+        Unhandled,
+    }
+
+    fn test_reducer() -> ReducerFn<i32, TestAction, (), TestEffect> {
+        |state, action| match action {
+            TestAction::Request => ReducerResult {
+                state: state.clone(),
+                events: vec![],
+                effects: vec![TestEffect::Request, TestEffect::Unhandled],
+            },
+            TestAction::Add(value) => ReducerResult {
+                state: Rc::new(**state + value),
+                events: vec![()],
+                effects: vec![],
+            },
+        }
+    }
+
+    struct UnhandledMiddleware(Rc<RefCell<Vec<TestEffect>>>);
+
+    impl Middleware<i32, TestAction, (), TestEffect> for UnhandledMiddleware {
+        fn process_effect(
+            &self,
+            _store: &Store<i32, TestAction, (), TestEffect>,
+            effect: TestEffect,
+        ) -> Option<TestEffect> {
+            self.0.borrow_mut().push(effect);
+            None
+        }
+    }
+
+    #[test]
+    fn test_async_effect() {
+        let store = StoreRef::new(test_reducer(), 0);
+        let mut pool = LocalPool::new();
+
+        let senders: Rc<RefCell<Vec<oneshot::Sender<i32>>>> = Rc::new(RefCell::new(Vec::new()));
+        let senders_local = senders.clone();
+        store.add_middleware(AsyncEffectMiddleware::new(
+            &store,
+            TestExecutor(pool.spawner()),
+            move |effect| match effect {
+                TestEffect::Request => {
+                    let (sender, receiver) = oneshot::channel();
+                    senders_local.borrow_mut().push(sender);
+                    Ok(Box::pin(async move {
+                        let value = receiver.await.unwrap();
+                        vec![TestAction::Add(value), TestAction::Add(value)]
+                    }))
+                }
+                effect => Err(effect),
+            },
+        ));
+        let unhandled = Rc::new(RefCell::new(Vec::new()));
+        store.add_middleware(UnhandledMiddleware(unhandled.clone()));
+
+        store.dispatch(TestAction::Request);
+        pool.run_until_stalled();
+        assert_eq!(0, *store.state());
+        assert_eq!(vec![TestEffect::Unhandled], *unhandled.borrow());
+
+        senders.borrow_mut().pop().unwrap().send(5).unwrap();
+        assert_eq!(0, *store.state());
+        pool.run_until_stalled();
+        assert_eq!(10, *store.state());
+    }
+
+    #[test]
+    fn test_async_effect_store_dropped() {
+        let store = StoreRef::new(test_reducer(), 0);
+        let mut pool = LocalPool::new();
+
+        store.add_middleware(AsyncEffectMiddleware::new(
+            &store,
+            TestExecutor(pool.spawner()),
+            |effect| match effect {
+                TestEffect::Request => Ok(Box::pin(async { vec![TestAction::Add(1)] })),
+                effect => Err(effect),
+            },
+        ));
+
+        store.dispatch(TestAction::Request);
+        drop(store);
+        pool.run_until_stalled();
+    }
+}
@@ -0,0 +1,947 @@
+//! [Middleware] which connects a [Store] to the
+//! [Redux DevTools](https://github.com/reduxjs/redux-devtools) using
+//! the remote (remotedev) protocol, so that the actions and state of
+//! the store can be inspected in the standard DevTools UI.
+//!
+//! The middleware connects to a
+//! [remotedev-server](https://github.com/zalmoxisus/remotedev-server)
+//! over a [DevToolsTransport], usually a [WebSocketTransport]. Every
+//! `Action` which is reduced is sent to the server along with the
+//! resulting `State`, both serialized as JSON using [serde]. Actions
+//! are sent as an object with a `type` (the `Action`'s
+//! [Display](std::fmt::Display) representation) and a `payload` (the
+//! serialized `Action`).
+//!
+//! The history of actions is recorded using a
+//! [TimeTravelMiddleware], which is used to respond to commands sent
+//! from the DevTools UI, including jumping to a state, skipping an
+//! action, importing and exporting the history, and dispatching an
+//! action. These commands are only processed when
+//! [poll()](DevToolsMiddleware::poll()) is called, which should be
+//! done regularly by the application, for example in its event loop.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use reactive_state::{
+//!     middleware::devtools::{DevToolsMiddleware, WebSocketTransport},
+//!     ReducerFn, ReducerResult, Store,
+//! };
+//! use std::rc::Rc;
+//!
+//! let reducer: ReducerFn<i32, i32, (), ()> = |state, action| ReducerResult {
+//!     state: Rc::new(**state + action),
+//!     events: vec![()],
+//!     effects: vec![],
+//! };
+//!
+//! let store = Store::new(reducer, 0);
+//! let transport =
+//!     WebSocketTransport::connect("ws://localhost:8000/socketcluster/?transport=websocket")
+//!         .unwrap();
+//! let devtools = DevToolsMiddleware::new(transport).name("counter");
+//! store.add_middleware(devtools.clone());
+//!
+//! store.dispatch(1);
+//!
+//! loop {
+//!     devtools.poll(&store).unwrap();
+//!     // ...
+//! }
+//! ```
+
+use super::{time_travel::TimeTravelMiddleware, Middleware, ReduceMiddlewareResult, ReduceNext};
+use crate::Store;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fmt::Display,
+    io,
+    net::TcpStream,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+/// The channel which the remotedev-server publishes commands from the
+/// DevTools UI to, for clients which log in as `"master"`.
+const RESPOND_CHANNEL: &str = "respond";
+
+/// An error which occurred while communicating with the DevTools.
+#[derive(Debug)]
+pub enum DevToolsError {
+    /// An error reading from or writing to the connection.
+    Io(io::Error),
+    /// An error in the WebSocket connection.
+    WebSocket(Box<tungstenite::Error>),
+    /// An error serializing or deserializing a message, `Action` or
+    /// `State`.
+    Serde(serde_json::Error),
+    /// A message received from the DevTools is not in the expected
+    /// format.
+    InvalidMessage(String),
+}
+
+impl Display for DevToolsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DevToolsError::Io(error) => write!(f, "devtools connection error: {}", error),
+            DevToolsError::WebSocket(error) => write!(f, "devtools websocket error: {}", error),
+            DevToolsError::Serde(error) => write!(f, "devtools serialization error: {}", error),
+            DevToolsError::InvalidMessage(message) => {
+                write!(f, "invalid devtools message: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DevToolsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DevToolsError::Io(error) => Some(error),
+            DevToolsError::WebSocket(error) => Some(error.as_ref()),
+            DevToolsError::Serde(error) => Some(error),
+            DevToolsError::InvalidMessage(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for DevToolsError {
+    fn from(error: io::Error) -> Self {
+        DevToolsError::Io(error)
+    }
+}
+
+impl From<tungstenite::Error> for DevToolsError {
+    fn from(error: tungstenite::Error) -> Self {
+        DevToolsError::WebSocket(Box::new(error))
+    }
+}
+
+impl From<serde_json::Error> for DevToolsError {
+    fn from(error: serde_json::Error) -> Self {
+        DevToolsError::Serde(error)
+    }
+}
+
+/// A connection to a remotedev-server, used by a
+/// [DevToolsMiddleware] to send and receive text messages.
+pub trait DevToolsTransport {
+    /// Send a text message to the server.
+    fn send(&mut self, message: String) -> Result<(), DevToolsError>;
+    /// Receive the next text message from the server without
+    /// blocking, or `None` if no message is available yet.
+    fn receive(&mut self) -> Result<Option<String>, DevToolsError>;
+}
+
+/// A [DevToolsTransport] which connects to a remotedev-server using a
+/// WebSocket.
+pub struct WebSocketTransport {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl WebSocketTransport {
+    /// Connect to the remotedev-server at `url`, for example
+    /// `"ws://localhost:8000/socketcluster/?transport=websocket"`.
+    /// This blocks until the WebSocket handshake has completed,
+    /// after which the connection is used without blocking.
+    pub fn connect(url: &str) -> Result<Self, DevToolsError> {
+        let (socket, _response) = tungstenite::connect(url)?;
+        if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            stream.set_nonblocking(true)?;
+        }
+        Ok(Self { socket })
+    }
+}
+
+fn would_block(error: &tungstenite::Error) -> bool {
+    matches!(error, tungstenite::Error::Io(error) if error.kind() == io::ErrorKind::WouldBlock)
+}
+
+impl DevToolsTransport for WebSocketTransport {
+    fn send(&mut self, message: String) -> Result<(), DevToolsError> {
+        match self.socket.send(Message::Text(message)) {
+            // The message has been buffered, and is written during a
+            // later send() or receive().
+            Err(error) if would_block(&error) => Ok(()),
+            result => Ok(result?),
+        }
+    }
+
+    fn receive(&mut self) -> Result<Option<String>, DevToolsError> {
+        loop {
+            match self.socket.read() {
+                Ok(Message::Text(message)) => return Ok(Some(message)),
+                // Pings are answered by tungstenite.
+                Ok(_) => continue,
+                Err(error) if would_block(&error) => return Ok(None),
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+}
+
+type ErrorFn = dyn Fn(&DevToolsError);
+
+struct DevToolsInner<T> {
+    transport: T,
+    name: String,
+    on_error: Option<Box<ErrorFn>>,
+    /// Whether the SocketCluster handshake and login have been sent.
+    connected: bool,
+    /// Whether the initial state has been sent.
+    initialized: bool,
+    /// The id of the next SocketCluster event which is emitted.
+    next_cid: u64,
+    /// The time (in milliseconds since the Unix epoch) that each
+    /// action in the [TimeTravelMiddleware] history was dispatched.
+    timestamps: Vec<u64>,
+}
+
+impl<T: DevToolsTransport> DevToolsInner<T> {
+    /// Emit a SocketCluster event.
+    fn emit(&mut self, event: &str, data: Value) -> Result<(), DevToolsError> {
+        let cid = self.next_cid;
+        self.next_cid += 1;
+        let message = json!({ "event": event, "data": data, "cid": cid });
+        self.transport.send(message.to_string())
+    }
+
+    /// Perform the SocketCluster handshake, log in as an application
+    /// (`"master"`), and subscribe to the commands sent from the
+    /// DevTools UI.
+    fn connect(&mut self) -> Result<(), DevToolsError> {
+        if self.connected {
+            return Ok(());
+        }
+        self.emit("#handshake", json!({ "authToken": null }))?;
+        self.emit("login", json!("master"))?;
+        self.emit("#subscribe", json!({ "channel": RESPOND_CHANNEL }))?;
+        self.connected = true;
+        Ok(())
+    }
+
+    /// Send a message of the specified `message_type` to the DevTools
+    /// UI, with the additional `fields`.
+    fn relay(&mut self, message_type: &str, fields: Value) -> Result<(), DevToolsError> {
+        self.connect()?;
+        let mut message = json!({
+            "type": message_type,
+            "id": self.name,
+            "name": self.name,
+            "instanceId": self.name,
+        });
+        if let (Some(message), Value::Object(fields)) = (message.as_object_mut(), fields) {
+            message.extend(fields);
+        }
+        self.emit("log", message)
+    }
+
+    fn report(&self, result: Result<(), DevToolsError>) {
+        if let (Err(error), Some(on_error)) = (result, &self.on_error) {
+            on_error(&error);
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn invalid(message: &str) -> DevToolsError {
+    DevToolsError::InvalidMessage(message.to_string())
+}
+
+/// Encode an `Action` in the form expected by the DevTools, with its
+/// [Display] representation as the `type`.
+fn encode_action<Action: Serialize + Display>(action: &Action) -> Result<Value, DevToolsError> {
+    Ok(json!({
+        "type": action.to_string(),
+        "payload": serde_json::to_value(action)?,
+    }))
+}
+
+/// Decode an `Action` encoded with [encode_action()]. Actions typed
+/// into the DevTools UI may also omit the `payload`, in which case
+/// the `type` is decoded as the action, or be a JSON string.
+fn decode_action<Action: DeserializeOwned>(action: &Value) -> Result<Action, DevToolsError> {
+    match action {
+        Value::String(action) => decode_action(&serde_json::from_str(action)?),
+        Value::Object(fields) => {
+            let payload = fields
+                .get("payload")
+                .or_else(|| fields.get("type"))
+                .ok_or_else(|| invalid("action has no payload or type"))?;
+            Ok(serde_json::from_value(payload.clone())?)
+        }
+        _ => Err(invalid("action is not an object")),
+    }
+}
+
+/// [Middleware] which connects a [Store] to the Redux DevTools using
+/// the remote (remotedev) protocol.
+///
+/// This middleware is a cheap handle to a shared connection, so a
+/// clone can be added to the store while another is kept to call
+/// [poll()](DevToolsMiddleware::poll()).
+///
+/// See [devtools](super::devtools) for more details.
+pub struct DevToolsMiddleware<State, Action, Event, Effect, T> {
+    inner: Rc<RefCell<DevToolsInner<T>>>,
+    time_travel: TimeTravelMiddleware<State, Action, Event, Effect>,
+}
+
+impl<State, Action, Event, Effect, T> DevToolsMiddleware<State, Action, Event, Effect, T>
+where
+    State: Serialize + DeserializeOwned,
+    Action: Serialize + DeserializeOwned + Display + Clone,
+    Event: Clone,
+    Effect: Clone,
+    T: DevToolsTransport,
+{
+    /// Create a new [DevToolsMiddleware] which communicates with the
+    /// DevTools using `transport`. Nothing is sent until the first
+    /// action is dispatched, or [poll()](DevToolsMiddleware::poll())
+    /// is called.
+    pub fn new(transport: T) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(DevToolsInner {
+                transport,
+                name: "reactive-state".to_string(),
+                on_error: None,
+                connected: false,
+                initialized: false,
+                next_cid: 1,
+                timestamps: Vec::new(),
+            })),
+            time_travel: TimeTravelMiddleware::new(),
+        }
+    }
+
+    /// Set the name which the store's instance is displayed with in
+    /// the DevTools UI. By default this is `"reactive-state"`.
+    pub fn name<S: Into<String>>(self, name: S) -> Self {
+        self.inner.borrow_mut().name = name.into();
+        self
+    }
+
+    /// Set a function to be called when an error occurs while
+    /// sending an action to the DevTools during a dispatch. By
+    /// default these errors are ignored.
+    pub fn on_error<F: Fn(&DevToolsError) + 'static>(self, on_error: F) -> Self {
+        self.inner.borrow_mut().on_error = Some(Box::new(on_error));
+        self
+    }
+
+    /// The [TimeTravelMiddleware] which records the history of
+    /// actions displayed in the DevTools.
+    pub fn time_travel(&self) -> &TimeTravelMiddleware<State, Action, Event, Effect> {
+        &self.time_travel
+    }
+
+    /// Send the `store`'s current state to the DevTools, if this has
+    /// not been done already.
+    fn init(&self, store: &Store<State, Action, Event, Effect>) -> Result<(), DevToolsError> {
+        let mut inner = self.inner.borrow_mut();
+        if inner.initialized {
+            return Ok(());
+        }
+        inner.initialized = true;
+        let payload = serde_json::to_string(&*store.state())?;
+        inner.relay("INIT", json!({ "payload": payload }))
+    }
+
+    /// Send the action which was most recently recorded by the
+    /// [TimeTravelMiddleware] to the DevTools.
+    fn relay_action(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+    ) -> Result<(), DevToolsError> {
+        let entry = match self.time_travel.last_entry() {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+        let len = self.time_travel.len();
+
+        let mut inner = self.inner.borrow_mut();
+        let timestamp = now();
+        // Dispatching after moving back in time discards the actions
+        // after the current position.
+        inner.timestamps.truncate(len - 1);
+        inner.timestamps.push(timestamp);
+
+        let action = json!({
+            "type": "PERFORM_ACTION",
+            "action": encode_action(&entry.action)?,
+            "timestamp": timestamp,
+        });
+        inner.relay(
+            "ACTION",
+            json!({
+                "action": action.to_string(),
+                "payload": serde_json::to_string(&*store.state())?,
+                "nextActionId": len + 1,
+            }),
+        )
+    }
+
+    /// The history of actions in the form of the Redux DevTools
+    /// "lifted state". Action id `0` is the initial state.
+    fn lifted_state(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+    ) -> Result<Value, DevToolsError> {
+        let entries = self.time_travel.entries();
+        let initial = self
+            .time_travel
+            .state_at(0)
+            .unwrap_or_else(|| store.state());
+        let initial = serde_json::to_value(&*initial)?;
+        let inner = self.inner.borrow();
+
+        let mut actions_by_id = Map::new();
+        actions_by_id.insert(
+            "0".to_string(),
+            json!({ "type": "PERFORM_ACTION", "action": { "type": "@@INIT" }, "timestamp": 0 }),
+        );
+        let mut computed_states = vec![json!({ "state": initial })];
+        let mut skipped_action_ids = Vec::new();
+
+        for (index, entry) in entries.iter().enumerate() {
+            let id = index + 1;
+            actions_by_id.insert(
+                id.to_string(),
+                json!({
+                    "type": "PERFORM_ACTION",
+                    "action": encode_action(&entry.action)?,
+                    "timestamp": inner.timestamps.get(index).copied().unwrap_or(0),
+                }),
+            );
+            computed_states.push(json!({ "state": serde_json::to_value(&*entry.state)? }));
+            if entry.skipped {
+                skipped_action_ids.push(id);
+            }
+        }
+
+        Ok(json!({
+            "actionsById": actions_by_id,
+            "computedStates": computed_states,
+            "committedState": initial,
+            "currentStateIndex": self.time_travel.position(),
+            "nextActionId": entries.len() + 1,
+            "skippedActionIds": skipped_action_ids,
+            "stagedActionIds": (0..=entries.len()).collect::<Vec<usize>>(),
+            "isLocked": false,
+            "isPaused": false,
+        }))
+    }
+
+    fn relay_state(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+    ) -> Result<(), DevToolsError> {
+        let payload = self.lifted_state(store)?.to_string();
+        self.inner
+            .borrow_mut()
+            .relay("STATE", json!({ "payload": payload }))
+    }
+
+    /// Replace the recorded history with a Redux DevTools "lifted
+    /// state", and move the `store` to the end of it.
+    fn import(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        lifted_state: &Value,
+    ) -> Result<(), DevToolsError> {
+        let lifted_state = match lifted_state {
+            Value::String(lifted_state) => serde_json::from_str(lifted_state)?,
+            lifted_state => lifted_state.clone(),
+        };
+
+        let initial = lifted_state
+            .get("committedState")
+            .or_else(|| lifted_state.pointer("/computedStates/0/state"))
+            .ok_or_else(|| invalid("imported state has no committed state"))?;
+        let initial: State = serde_json::from_value(initial.clone())?;
+
+        let ids = lifted_state
+            .get("stagedActionIds")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("imported state has no staged action ids"))?;
+        let skipped: HashSet<u64> = lifted_state
+            .get("skippedActionIds")
+            .and_then(Value::as_array)
+            .map(|ids| ids.iter().filter_map(Value::as_u64).collect())
+            .unwrap_or_default();
+
+        let mut actions = Vec::new();
+        let mut timestamps = Vec::new();
+        for id in ids.iter().filter_map(Value::as_u64).filter(|id| *id != 0) {
+            let lifted_action = lifted_state
+                .get("actionsById")
+                .and_then(|actions| actions.get(id.to_string()))
+                .ok_or_else(|| invalid("imported state is missing a staged action"))?;
+            let action = lifted_action
+                .get("action")
+                .ok_or_else(|| invalid("imported action is empty"))?;
+            actions.push((decode_action(action)?, skipped.contains(&id)));
+            timestamps.push(
+                lifted_action
+                    .get("timestamp")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0),
+            );
+        }
+
+        self.inner.borrow_mut().timestamps = timestamps;
+        self.time_travel.import(store, Rc::new(initial), actions);
+        Ok(())
+    }
+
+    /// Process a lifted action sent from the DevTools UI with a
+    /// `DISPATCH` message.
+    fn dispatch_lifted(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        action: &Value,
+    ) -> Result<(), DevToolsError> {
+        let index = |field: &str| {
+            action
+                .get(field)
+                .and_then(Value::as_u64)
+                .map(|index| index as usize)
+                .ok_or_else(|| invalid("lifted action has no index"))
+        };
+
+        match action.get("type").and_then(Value::as_str) {
+            Some("JUMP_TO_STATE") => {
+                self.time_travel.jump_to(store, index("index")?);
+            }
+            Some("JUMP_TO_ACTION") => {
+                self.time_travel.jump_to(store, index("actionId")?);
+            }
+            Some("TOGGLE_ACTION") => {
+                // Action id 0 is the initial state, which can't be
+                // skipped.
+                if let Some(index) = index("id")?.checked_sub(1) {
+                    self.time_travel.toggle_action(store, index);
+                }
+                self.relay_state(store)?;
+            }
+            Some("IMPORT_STATE") => {
+                let lifted_state = action
+                    .get("nextLiftedState")
+                    .ok_or_else(|| invalid("import has no state"))?;
+                self.import(store, lifted_state)?;
+                self.relay_state(store)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Process a message sent from the DevTools UI.
+    fn handle(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        message: &Value,
+    ) -> Result<(), DevToolsError> {
+        match message.get("type").and_then(Value::as_str) {
+            Some("START") | Some("UPDATE") => self.relay_state(store),
+            Some("DISPATCH") => {
+                let action = message
+                    .get("action")
+                    .ok_or_else(|| invalid("dispatch has no action"))?;
+                self.dispatch_lifted(store, action)
+            }
+            Some("ACTION") => {
+                let action = message
+                    .get("action")
+                    .ok_or_else(|| invalid("action message has no action"))?;
+                store.dispatch(decode_action::<Action>(action)?);
+                Ok(())
+            }
+            Some("IMPORT") => {
+                let lifted_state = message
+                    .get("state")
+                    .ok_or_else(|| invalid("import has no state"))?;
+                self.import(store, lifted_state)?;
+                self.relay_state(store)
+            }
+            Some("EXPORT") => {
+                let lifted_state = self.lifted_state(store)?;
+                let actions: Vec<&Value> = lifted_state["actionsById"]
+                    .as_object()
+                    .map(|actions| actions.values().collect())
+                    .unwrap_or_default();
+                let payload = serde_json::to_string(&actions)?;
+                let committed_state = lifted_state["committedState"].to_string();
+                self.inner.borrow_mut().relay(
+                    "EXPORT",
+                    json!({ "payload": payload, "committedState": committed_state }),
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Receive and process the commands which have been sent from the
+    /// DevTools UI, such as jumping to a state, skipping an action,
+    /// importing and exporting the history, or dispatching an action
+    /// to the `store`. Returns the number of commands which were
+    /// processed.
+    ///
+    /// This does not block, and should be called regularly by the
+    /// application.
+    pub fn poll(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+    ) -> Result<usize, DevToolsError> {
+        self.init(store)?;
+
+        let mut messages = Vec::new();
+        {
+            let mut inner = self.inner.borrow_mut();
+            while let Some(message) = inner.transport.receive()? {
+                // SocketCluster ping.
+                if message == "#1" {
+                    inner.transport.send("#2".to_string())?;
+                    continue;
+                }
+
+                let message: Value = serde_json::from_str(&message)?;
+                if message.get("event").and_then(Value::as_str) != Some("#publish") {
+                    // Acknowledgements and other SocketCluster events.
+                    continue;
+                }
+                if let Some(data) = message.get("data") {
+                    if data.get("channel").and_then(Value::as_str) == Some(RESPOND_CHANNEL) {
+                        if let Some(data) = data.get("data") {
+                            messages.push(data.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        // The connection is not borrowed while handling the messages,
+        // because listeners notified of changes to the state may
+        // dispatch actions.
+        for message in &messages {
+            self.handle(store, message)?;
+        }
+        Ok(messages.len())
+    }
+}
+
+impl<State, Action, Event, Effect, T> Clone
+    for DevToolsMiddleware<State, Action, Event, Effect, T>
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            time_travel: self.time_travel.clone(),
+        }
+    }
+}
+
+impl<State, Action, Event, Effect, T> Middleware<State, Action, Event, Effect>
+    for DevToolsMiddleware<State, Action, Event, Effect, T>
+where
+    State: Serialize + DeserializeOwned,
+    Action: Serialize + DeserializeOwned + Display + Clone,
+    Event: Clone,
+    Effect: Clone,
+    T: DevToolsTransport,
+{
+    fn on_reduce(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        action: Option<&Action>,
+        next: ReduceNext<'_, State, Action, Event, Effect>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        let result = self.init(store);
+        self.inner.borrow().report(result);
+
+        let reduce_result = self.time_travel.on_reduce(store, action, next);
+
+        // Actions rejected by the reducer are not recorded.
+        if action.is_some() && !store.reduce_rejected() {
+            let result = self.relay_action(store);
+            self.inner.borrow().report(result);
+        }
+
+        reduce_result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DevToolsError, DevToolsMiddleware, DevToolsTransport, WebSocketTransport};
+    use crate::{ReducerFn, ReducerResult, Store, TryReducerFn};
+    use serde_json::{json, Value};
+    use std::{
+        cell::RefCell,
+        collections::VecDeque,
+        net::TcpListener,
+        rc::Rc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    /// A [DevToolsTransport] which records sent messages, and
+    /// receives messages pushed onto a queue.
+    #[derive(Clone, Default)]
+    struct TestTransport {
+        sent: Rc<RefCell<Vec<Value>>>,
+        received: Rc<RefCell<VecDeque<String>>>,
+    }
+
+    impl TestTransport {
+        fn publish(&self, data: Value) {
+            let message = json!({
+                "event": "#publish",
+                "data": { "channel": "respond", "data": data },
+            });
+            self.received.borrow_mut().push_back(message.to_string());
+        }
+
+        /// The `log` messages which have been sent.
+        fn logs(&self) -> Vec<Value> {
+            self.sent
+                .borrow()
+                .iter()
+                .filter(|message| message["event"] == "log")
+                .map(|message| message["data"].clone())
+                .collect()
+        }
+    }
+
+    impl DevToolsTransport for TestTransport {
+        fn send(&mut self, message: String) -> Result<(), DevToolsError> {
+            let message = serde_json::from_str(&message).unwrap_or(Value::String(message));
+            self.sent.borrow_mut().push(message);
+            Ok(())
+        }
+
+        fn receive(&mut self) -> Result<Option<String>, DevToolsError> {
+            Ok(self.received.borrow_mut().pop_front())
+        }
+    }
+
+    fn test_reducer() -> ReducerFn<i32, i32, (), ()> {
+        |state, action| ReducerResult {
+            state: Rc::new(**state + action),
+            events: vec![()],
+            effects: vec![],
+        }
+    }
+
+    type TestDevTools<T> = DevToolsMiddleware<i32, i32, (), (), T>;
+
+    fn test_store() -> (
+        Store<i32, i32, (), ()>,
+        TestDevTools<TestTransport>,
+        TestTransport,
+    ) {
+        let store = Store::new(test_reducer(), 0);
+        let transport = TestTransport::default();
+        let devtools = DevToolsMiddleware::new(transport.clone()).name("test");
+        store.add_middleware(devtools.clone());
+        for action in &[1, 10, 100] {
+            store.dispatch(*action);
+        }
+        (store, devtools, transport)
+    }
+
+    #[test]
+    fn test_relay() {
+        let (_store, _devtools, transport) = test_store();
+
+        let sent = transport.sent.borrow();
+        assert_eq!("#handshake", sent[0]["event"]);
+        assert_eq!("login", sent[1]["event"]);
+        assert_eq!("master", sent[1]["data"]);
+        drop(sent);
+
+        let logs = transport.logs();
+        assert_eq!(4, logs.len());
+        assert_eq!("INIT", logs[0]["type"]);
+        assert_eq!("0", logs[0]["payload"]);
+        assert_eq!("ACTION", logs[2]["type"]);
+        assert_eq!("test", logs[2]["name"]);
+        assert_eq!("11", logs[2]["payload"]);
+        assert_eq!(3, logs[2]["nextActionId"]);
+
+        let action: Value = serde_json::from_str(logs[2]["action"].as_str().unwrap()).unwrap();
+        assert_eq!("PERFORM_ACTION", action["type"]);
+        assert_eq!(json!({ "type": "10", "payload": 10 }), action["action"]);
+    }
+
+    #[test]
+    fn test_rejected_action_not_relayed() {
+        let reducer: TryReducerFn<i32, i32, (), (), &str> = TryReducerFn(|state, action| {
+            if *action < 0 {
+                return Err("negative");
+            }
+            Ok(ReducerResult {
+                state: Rc::new(**state + action),
+                events: vec![()],
+                effects: vec![],
+            })
+        });
+        let store = Store::new_fallible(reducer, 0);
+        let transport = TestTransport::default();
+        let devtools = DevToolsMiddleware::new(transport.clone());
+        store.add_middleware(devtools.clone());
+
+        store.dispatch(1);
+        store.dispatch(-1);
+        store.dispatch(10);
+
+        let logs = transport.logs();
+        assert_eq!(3, logs.len());
+        assert_eq!("1", logs[1]["payload"]);
+        assert_eq!("11", logs[2]["payload"]);
+        assert_eq!(3, logs[2]["nextActionId"]);
+    }
+
+    #[test]
+    fn test_jump_to_state() {
+        let (store, devtools, transport) = test_store();
+
+        transport.received.borrow_mut().push_back("#1".to_string());
+        transport.publish(json!({
+            "type": "DISPATCH",
+            "action": { "type": "JUMP_TO_STATE", "index": 1 },
+        }));
+        assert_eq!(1, devtools.poll(&store).unwrap());
+        assert_eq!(1, *store.state());
+        assert!(transport
+            .sent
+            .borrow()
+            .contains(&Value::String("#2".to_string())));
+
+        transport.publish(json!({
+            "type": "DISPATCH",
+            "action": { "type": "JUMP_TO_ACTION", "actionId": 3 },
+        }));
+        devtools.poll(&store).unwrap();
+        assert_eq!(111, *store.state());
+    }
+
+    #[test]
+    fn test_toggle_action() {
+        let (store, devtools, transport) = test_store();
+
+        transport.publish(json!({
+            "type": "DISPATCH",
+            "action": { "type": "TOGGLE_ACTION", "id": 2 },
+        }));
+        devtools.poll(&store).unwrap();
+        assert_eq!(101, *store.state());
+
+        let logs = transport.logs();
+        let state = logs.last().unwrap();
+        assert_eq!("STATE", state["type"]);
+        let lifted_state: Value = serde_json::from_str(state["payload"].as_str().unwrap()).unwrap();
+        assert_eq!(json!([2]), lifted_state["skippedActionIds"]);
+        assert_eq!(json!({ "state": 101 }), lifted_state["computedStates"][3]);
+    }
+
+    #[test]
+    fn test_export_import() {
+        let (store, devtools, transport) = test_store();
+
+        transport.publish(json!({ "type": "START" }));
+        devtools.poll(&store).unwrap();
+        let exported = transport.logs().last().unwrap()["payload"].clone();
+
+        transport.publish(json!({ "type": "EXPORT" }));
+        devtools.poll(&store).unwrap();
+        let export = transport.logs().last().unwrap().clone();
+        assert_eq!("EXPORT", export["type"]);
+        assert_eq!("0", export["committedState"]);
+
+        let (store, devtools, transport) = {
+            let store = Store::new(test_reducer(), 5);
+            let transport = TestTransport::default();
+            let devtools: TestDevTools<_> = DevToolsMiddleware::new(transport.clone());
+            store.add_middleware(devtools.clone());
+            (store, devtools, transport)
+        };
+
+        transport.publish(json!({ "type": "IMPORT", "state": exported }));
+        devtools.poll(&store).unwrap();
+        assert_eq!(111, *store.state());
+        assert_eq!(3, devtools.time_travel().len());
+
+        transport
+            .publish(json!({ "type": "ACTION", "action": "{\"type\": \"5\", \"payload\": 5}" }));
+        devtools.poll(&store).unwrap();
+        assert_eq!(116, *store.state());
+    }
+
+    #[test]
+    fn test_websocket() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // A stand-in for the remotedev-server, which waits for the
+        // client to log in and send an action, and then jumps back to
+        // the initial state.
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            let mut events = Vec::new();
+            while events.len() < 5 {
+                let message = socket.read().unwrap();
+                let message: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+                events.push(message["event"].as_str().unwrap().to_string());
+            }
+
+            let command = json!({
+                "event": "#publish",
+                "data": {
+                    "channel": "respond",
+                    "data": {
+                        "type": "DISPATCH",
+                        "action": { "type": "JUMP_TO_STATE", "index": 0 },
+                    },
+                },
+            });
+            socket
+                .send(tungstenite::Message::Text(command.to_string()))
+                .unwrap();
+
+            while socket.read().is_ok() {}
+            events
+        });
+
+        let store = Store::new(test_reducer(), 0);
+        let transport = WebSocketTransport::connect(&format!("ws://{}", address)).unwrap();
+        let devtools = DevToolsMiddleware::new(transport);
+        store.add_middleware(devtools.clone());
+        store.dispatch(1);
+
+        let start = Instant::now();
+        while devtools.poll(&store).unwrap() == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(0, *store.state());
+
+        drop(devtools);
+        drop(store);
+        assert_eq!(
+            vec!["#handshake", "login", "#subscribe", "log", "log"],
+            server.join().unwrap()
+        );
+    }
+}
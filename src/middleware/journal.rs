@@ -0,0 +1,829 @@
+//! [Middleware] which records every `Action` dispatched to a [Store]
+//! in an append-only [Journal], so that the state can be
+//! reconstructed by replaying the actions through the
+//! [Reducer](crate::Reducer) using [Store::replay()].
+//!
+//! Each action is recorded with a sequence number and a timestamp,
+//! encoded using a [JournalCodec], and appended to a [JournalLog].
+//! To stop the journal from growing forever, a snapshot of the state
+//! can be recorded periodically using
+//! [snapshot_every()](JournalMiddleware::snapshot_every()), which
+//! truncates the records before it. Replaying then starts from the
+//! most recent snapshot.
+//!
+//! On a store created with [Store::new_fallible()], an action which
+//! the [Reducer](crate::Reducer) rejects is followed in the journal by
+//! a [JournalRecordKind::Rejected] record, and is neither replayed nor
+//! counted towards [snapshot_every()](JournalMiddleware::snapshot_every()).
+//!
+//! The actions are recorded as they reach the [JournalMiddleware] in
+//! the middleware chain, so it should be the last middleware in the
+//! chain (see [MiddlewarePosition::Last](super::MiddlewarePosition::Last)).
+//! Otherwise, actions replaced or removed by middleware after it
+//! would be recorded instead of the actions which were actually
+//! reduced, and the replayed state would diverge from the store's.
+//!
+//! ## Example
+//!
+//! ```
+//! use reactive_state::{
+//!     middleware::journal::{Journal, JournalCodec, JournalError, JournalMiddleware, MemoryLog},
+//!     ReducerFn, ReducerResult, Store,
+//! };
+//! use std::{convert::TryInto, rc::Rc};
+//!
+//! struct Codec;
+//!
+//! impl JournalCodec<u32, u32> for Codec {
+//!     fn encode_action(&self, action: &u32) -> Result<Vec<u8>, JournalError> {
+//!         Ok(action.to_le_bytes().to_vec())
+//!     }
+//!
+//!     fn decode_action(&self, data: &[u8]) -> Result<u32, JournalError> {
+//!         let bytes = data.try_into().map_err(|error| JournalError::Codec(Box::new(error)))?;
+//!         Ok(u32::from_le_bytes(bytes))
+//!     }
+//!
+//!     fn encode_state(&self, state: &u32) -> Result<Vec<u8>, JournalError> {
+//!         self.encode_action(state)
+//!     }
+//!
+//!     fn decode_state(&self, data: &[u8]) -> Result<u32, JournalError> {
+//!         self.decode_action(data)
+//!     }
+//! }
+//!
+//! let reducer: ReducerFn<u32, u32, (), ()> = |state, action| ReducerResult {
+//!     state: Rc::new(**state + action),
+//!     events: vec![],
+//!     effects: vec![],
+//! };
+//!
+//! let journal = Journal::new(Codec, MemoryLog::new());
+//! let store = Store::new(reducer, 0);
+//! store.add_middleware(JournalMiddleware::new(journal.clone()).snapshot_every(2));
+//! store.dispatch(1u32);
+//! store.dispatch(2u32);
+//! store.dispatch(3u32);
+//! drop(store);
+//!
+//! // After a crash, the state is reconstructed from the journal.
+//! let store = Store::replay(reducer, 0, &journal).unwrap();
+//! assert_eq!(6, *store.state());
+//! ```
+
+use super::{Middleware, ReduceMiddlewareResult, ReduceNext};
+use crate::{Reducer, Store};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    convert::{TryFrom, TryInto},
+    fmt::Display,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    marker::PhantomData,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// An error which occurred while writing to or replaying a
+/// [Journal].
+#[derive(Debug)]
+pub enum JournalError {
+    /// An error reading from or writing to the [JournalLog].
+    Io(io::Error),
+    /// An error encoding or decoding an `Action` or `State` using the
+    /// [JournalCodec].
+    Codec(Box<dyn std::error::Error>),
+}
+
+impl Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalError::Io(error) => write!(f, "journal log error: {}", error),
+            JournalError::Codec(error) => write!(f, "journal codec error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JournalError::Io(error) => Some(error),
+            JournalError::Codec(error) => Some(error.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for JournalError {
+    fn from(error: io::Error) -> Self {
+        JournalError::Io(error)
+    }
+}
+
+/// Encodes and decodes the actions and state snapshots recorded in a
+/// [Journal].
+pub trait JournalCodec<State, Action> {
+    fn encode_action(&self, action: &Action) -> Result<Vec<u8>, JournalError>;
+    fn decode_action(&self, data: &[u8]) -> Result<Action, JournalError>;
+    fn encode_state(&self, state: &State) -> Result<Vec<u8>, JournalError>;
+    fn decode_state(&self, data: &[u8]) -> Result<State, JournalError>;
+}
+
+/// What a [JournalRecord] contains.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JournalRecordKind {
+    /// An encoded `Action`.
+    Action,
+    /// An encoded snapshot of the `State`.
+    Snapshot,
+    /// A marker recording that the action with the same sequence
+    /// number was rejected by the [Reducer](crate::Reducer). It has
+    /// no data.
+    Rejected,
+}
+
+/// A record in a [JournalLog].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalRecord {
+    pub kind: JournalRecordKind,
+    /// For an action, its position in the sequence of actions which
+    /// have been dispatched, and for a rejection, the position of the
+    /// rejected action. For a snapshot, the number of actions
+    /// which had been dispatched when it was taken, which is the
+    /// sequence number of the next action.
+    pub sequence: u64,
+    /// When the record was created.
+    pub timestamp: SystemTime,
+    /// The encoded action or state.
+    pub data: Vec<u8>,
+}
+
+/// An append-only log which stores the records of a [Journal].
+pub trait JournalLog {
+    /// Append a `record` to the end of the log.
+    fn append(&self, record: &JournalRecord) -> io::Result<()>;
+    /// All the records in the log, in the order they were appended.
+    fn records(&self) -> io::Result<Vec<JournalRecord>>;
+    /// Remove all records with a sequence number less than
+    /// `sequence`.
+    fn truncate(&self, sequence: u64) -> io::Result<()>;
+}
+
+/// A [JournalLog] which is kept in memory, intended for use in
+/// tests. Clones of this log share the same records.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLog {
+    records: Rc<RefCell<Vec<JournalRecord>>>,
+}
+
+impl MemoryLog {
+    /// Create a new empty [MemoryLog].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl JournalLog for MemoryLog {
+    fn append(&self, record: &JournalRecord) -> io::Result<()> {
+        self.records.borrow_mut().push(record.clone());
+        Ok(())
+    }
+
+    fn records(&self) -> io::Result<Vec<JournalRecord>> {
+        Ok(self.records.borrow().clone())
+    }
+
+    fn truncate(&self, sequence: u64) -> io::Result<()> {
+        self.records
+            .borrow_mut()
+            .retain(|record| record.sequence >= sequence);
+        Ok(())
+    }
+}
+
+/// The size of the header written before the data of each record in
+/// a [FileLog]: the kind, sequence, timestamp seconds, timestamp
+/// nanoseconds and data length.
+const FILE_HEADER_LEN: usize = 1 + 8 + 8 + 4 + 4;
+
+/// A [JournalLog] which is stored in a file on the file system.
+///
+/// Records are written in a simple binary format. If the application
+/// crashes while a record is being appended, the incomplete record at
+/// the end of the file is ignored when it is read, and removed before
+/// the next record is appended.
+#[derive(Debug, Clone)]
+pub struct FileLog {
+    path: PathBuf,
+    /// Whether an incomplete record at the end of the file has been
+    /// checked for since this log was created.
+    repaired: Cell<bool>,
+}
+
+impl FileLog {
+    /// Create a new [FileLog] which is stored in the file at `path`.
+    /// The file is created when the first record is appended.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            repaired: Cell::new(false),
+        }
+    }
+
+    /// Remove an incomplete record from the end of the file, left by
+    /// a crash during [append()](JournalLog::append()).
+    fn repair(&self) -> io::Result<()> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        let (_, len) = Self::decode(&data)?;
+        if len < data.len() {
+            OpenOptions::new()
+                .write(true)
+                .open(&self.path)?
+                .set_len(len as u64)?;
+        }
+        Ok(())
+    }
+
+    /// Encode the length of a record's data, which must fit in a
+    /// `u32`.
+    fn encode_len(len: usize) -> io::Result<[u8; 4]> {
+        u32::try_from(len).map(u32::to_le_bytes).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("journal record data is too large ({} bytes)", len),
+            )
+        })
+    }
+
+    fn encode(record: &JournalRecord, buffer: &mut Vec<u8>) -> io::Result<()> {
+        let len = Self::encode_len(record.data.len())?;
+        let timestamp = record
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        buffer.push(match record.kind {
+            JournalRecordKind::Action => 0,
+            JournalRecordKind::Snapshot => 1,
+            JournalRecordKind::Rejected => 2,
+        });
+        buffer.extend_from_slice(&record.sequence.to_le_bytes());
+        buffer.extend_from_slice(&timestamp.as_secs().to_le_bytes());
+        buffer.extend_from_slice(&timestamp.subsec_nanos().to_le_bytes());
+        buffer.extend_from_slice(&len);
+        buffer.extend_from_slice(&record.data);
+        Ok(())
+    }
+
+    /// Decode the records in `data`, also returning the length of the
+    /// data which contains complete records.
+    fn decode(data: &[u8]) -> io::Result<(Vec<JournalRecord>, usize)> {
+        let mut records = Vec::new();
+        let mut remaining = data;
+        while remaining.len() >= FILE_HEADER_LEN {
+            let (header, rest) = remaining.split_at(FILE_HEADER_LEN);
+            let kind = match header[0] {
+                0 => JournalRecordKind::Action,
+                1 => JournalRecordKind::Snapshot,
+                2 => JournalRecordKind::Rejected,
+                kind => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid journal record kind {}", kind),
+                    ))
+                }
+            };
+            let sequence = u64::from_le_bytes(header[1..9].try_into().unwrap());
+            let secs = u64::from_le_bytes(header[9..17].try_into().unwrap());
+            let nanos = u32::from_le_bytes(header[17..21].try_into().unwrap());
+            let len = u32::from_le_bytes(header[21..25].try_into().unwrap()) as usize;
+
+            if rest.len() < len {
+                // An incomplete record at the end of the file.
+                break;
+            }
+
+            let (record_data, rest) = rest.split_at(len);
+            records.push(JournalRecord {
+                kind,
+                sequence,
+                timestamp: UNIX_EPOCH + Duration::new(secs, nanos),
+                data: record_data.to_vec(),
+            });
+            remaining = rest;
+        }
+        Ok((records, data.len() - remaining.len()))
+    }
+}
+
+impl JournalLog for FileLog {
+    fn append(&self, record: &JournalRecord) -> io::Result<()> {
+        if !self.repaired.get() {
+            self.repair()?;
+            self.repaired.set(true);
+        }
+
+        let mut buffer = Vec::new();
+        Self::encode(record, &mut buffer)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&buffer)
+    }
+
+    fn records(&self) -> io::Result<Vec<JournalRecord>> {
+        match fs::read(&self.path) {
+            Ok(data) => Self::decode(&data).map(|(records, _)| records),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn truncate(&self, sequence: u64) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        for record in self.records()? {
+            if record.sequence >= sequence {
+                Self::encode(&record, &mut buffer)?;
+            }
+        }
+
+        // Write to a temporary file first, so that the log is never
+        // left partially written.
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, buffer)?;
+        fs::rename(&temp_path, &self.path)
+    }
+}
+
+struct JournalInner<C, L> {
+    codec: C,
+    log: L,
+    /// The sequence number for the next action, read from the log
+    /// when it is first required.
+    next_sequence: Cell<Option<u64>>,
+}
+
+/// An append-only journal of the actions dispatched to a [Store],
+/// which uses a [JournalCodec] to encode the actions, and stores them
+/// in a [JournalLog].
+///
+/// Clones of this journal share the same log.
+pub struct Journal<State, Action, C, L> {
+    inner: Rc<JournalInner<C, L>>,
+    phantom: PhantomData<(State, Action)>,
+}
+
+impl<State, Action, C, L> Journal<State, Action, C, L>
+where
+    C: JournalCodec<State, Action>,
+    L: JournalLog,
+{
+    /// Create a new [Journal] which encodes records using `codec`,
+    /// and stores them in `log`. If the `log` already contains
+    /// records, new actions continue on from its sequence numbers.
+    pub fn new(codec: C, log: L) -> Self {
+        Self {
+            inner: Rc::new(JournalInner {
+                codec,
+                log,
+                next_sequence: Cell::new(None),
+            }),
+            phantom: PhantomData,
+        }
+    }
+
+    /// The codec used to encode the records in this journal.
+    pub fn codec(&self) -> &C {
+        &self.inner.codec
+    }
+
+    /// The log which the records in this journal are stored in.
+    pub fn log(&self) -> &L {
+        &self.inner.log
+    }
+
+    /// The sequence number which will be given to the next action.
+    pub fn next_sequence(&self) -> Result<u64, JournalError> {
+        if let Some(sequence) = self.inner.next_sequence.get() {
+            return Ok(sequence);
+        }
+
+        let sequence = self
+            .inner
+            .log
+            .records()?
+            .iter()
+            .map(|record| match record.kind {
+                JournalRecordKind::Action | JournalRecordKind::Rejected => record.sequence + 1,
+                JournalRecordKind::Snapshot => record.sequence,
+            })
+            .max()
+            .unwrap_or(0);
+        self.inner.next_sequence.set(Some(sequence));
+        Ok(sequence)
+    }
+
+    /// Append an `action` to the journal, returning its sequence
+    /// number.
+    pub fn append(&self, action: &Action) -> Result<u64, JournalError> {
+        let sequence = self.next_sequence()?;
+        self.inner.log.append(&JournalRecord {
+            kind: JournalRecordKind::Action,
+            sequence,
+            timestamp: SystemTime::now(),
+            data: self.inner.codec.encode_action(action)?,
+        })?;
+        self.inner.next_sequence.set(Some(sequence + 1));
+        Ok(sequence)
+    }
+
+    /// Record that the action with the specified `sequence` number
+    /// was rejected by the [Reducer](crate::Reducer), so that it is
+    /// skipped by [replay()](Journal::replay()).
+    pub fn reject(&self, sequence: u64) -> Result<(), JournalError> {
+        self.inner.log.append(&JournalRecord {
+            kind: JournalRecordKind::Rejected,
+            sequence,
+            timestamp: SystemTime::now(),
+            data: Vec::new(),
+        })?;
+        Ok(())
+    }
+
+    /// Append a snapshot of the `state` after all the actions in the
+    /// journal so far, and remove the records before it from the log.
+    pub fn snapshot(&self, state: &State) -> Result<(), JournalError> {
+        let sequence = self.next_sequence()?;
+        self.inner.log.append(&JournalRecord {
+            kind: JournalRecordKind::Snapshot,
+            sequence,
+            timestamp: SystemTime::now(),
+            data: self.inner.codec.encode_state(state)?,
+        })?;
+        self.inner.log.truncate(sequence)?;
+        Ok(())
+    }
+
+    /// Reconstruct the state by running the actions in this journal
+    /// through the `reducer`, starting from the most recent snapshot,
+    /// or `initial_state` if there is no snapshot. The effects and
+    /// events produced by the `reducer` are discarded.
+    ///
+    /// Actions are reduced with [Reducer::try_reduce()]. Actions
+    /// which were recorded as rejected, or which the `reducer`
+    /// rejects, are skipped, leaving the state as it was.
+    pub fn replay<Event, Effect>(
+        &self,
+        reducer: &dyn Reducer<State, Action, Event, Effect>,
+        initial_state: Rc<State>,
+    ) -> Result<Rc<State>, JournalError> {
+        let records = self.inner.log.records()?;
+        let start = records
+            .iter()
+            .rposition(|record| record.kind == JournalRecordKind::Snapshot);
+
+        let (mut state, records) = match start {
+            Some(start) => (
+                Rc::new(self.inner.codec.decode_state(&records[start].data)?),
+                &records[start + 1..],
+            ),
+            None => (initial_state, &records[..]),
+        };
+
+        let rejected: HashSet<u64> = records
+            .iter()
+            .filter(|record| record.kind == JournalRecordKind::Rejected)
+            .map(|record| record.sequence)
+            .collect();
+
+        for record in records {
+            if record.kind == JournalRecordKind::Action && !rejected.contains(&record.sequence) {
+                let action = self.inner.codec.decode_action(&record.data)?;
+                if let Ok(result) = reducer.try_reduce(&state, &action) {
+                    state = result.state;
+                }
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+impl<State, Action, C, L> Clone for Journal<State, Action, C, L> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+type ErrorFn = dyn Fn(&JournalError);
+
+/// [Middleware] which appends every `Action` dispatched to a [Store]
+/// to a [Journal]. It should be added after all the other middleware
+/// which can modify actions.
+///
+/// See [journal](super::journal) for more details.
+pub struct JournalMiddleware<State, Action, C, L> {
+    journal: Journal<State, Action, C, L>,
+    snapshot_every: Option<usize>,
+    since_snapshot: Cell<usize>,
+    on_error: Option<Box<ErrorFn>>,
+}
+
+impl<State, Action, C, L> JournalMiddleware<State, Action, C, L>
+where
+    C: JournalCodec<State, Action>,
+    L: JournalLog,
+{
+    /// Create a new [JournalMiddleware] which appends actions to the
+    /// `journal`.
+    pub fn new(journal: Journal<State, Action, C, L>) -> Self {
+        Self {
+            journal,
+            snapshot_every: None,
+            since_snapshot: Cell::new(0),
+            on_error: None,
+        }
+    }
+
+    /// Take a snapshot of the state (and truncate the journal) after
+    /// every `actions` actions have been dispatched. By default no
+    /// snapshots are taken.
+    pub fn snapshot_every(mut self, actions: usize) -> Self {
+        self.snapshot_every = Some(actions);
+        self
+    }
+
+    /// Set a function to be called when an error occurs while writing
+    /// to the journal during a dispatch. By default these errors are
+    /// ignored.
+    pub fn on_error<F: Fn(&JournalError) + 'static>(mut self, on_error: F) -> Self {
+        self.on_error = Some(Box::new(on_error));
+        self
+    }
+
+    fn report(&self, result: Result<(), JournalError>) {
+        if let (Err(error), Some(on_error)) = (result, &self.on_error) {
+            on_error(&error);
+        }
+    }
+}
+
+impl<State, Action, Event, Effect, C, L> Middleware<State, Action, Event, Effect>
+    for JournalMiddleware<State, Action, C, L>
+where
+    Event: Clone,
+    C: JournalCodec<State, Action>,
+    L: JournalLog,
+{
+    fn on_reduce(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        action: Option<&Action>,
+        next: ReduceNext<'_, State, Action, Event, Effect>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        // The action is recorded before it is reduced, so that it is
+        // not lost if the application crashes in the reducer.
+        let sequence = action.and_then(|action| match self.journal.append(action) {
+            Ok(sequence) => Some(sequence),
+            Err(error) => {
+                self.report(Err(error));
+                None
+            }
+        });
+
+        let result = next.reduce(action);
+
+        if store.reduce_rejected() {
+            if let Some(sequence) = sequence {
+                self.report(self.journal.reject(sequence));
+            }
+            return result;
+        }
+
+        if let (Some(every), Some(_)) = (self.snapshot_every, action) {
+            let since_snapshot = self.since_snapshot.get() + 1;
+            if since_snapshot >= every {
+                self.since_snapshot.set(0);
+                self.report(self.journal.snapshot(&store.state()));
+            } else {
+                self.since_snapshot.set(since_snapshot);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FileLog, Journal, JournalCodec, JournalError, JournalLog, JournalMiddleware,
+        JournalRecordKind, MemoryLog,
+    };
+    use crate::{ReduceError, Reducer, ReducerFn, ReducerResult, Store};
+    use std::{convert::TryInto, rc::Rc};
+
+    struct TestCodec;
+
+    impl JournalCodec<i32, i32> for TestCodec {
+        fn encode_action(&self, action: &i32) -> Result<Vec<u8>, JournalError> {
+            Ok(action.to_le_bytes().to_vec())
+        }
+
+        fn decode_action(&self, data: &[u8]) -> Result<i32, JournalError> {
+            let bytes = data
+                .try_into()
+                .map_err(|error| JournalError::Codec(Box::new(error)))?;
+            Ok(i32::from_le_bytes(bytes))
+        }
+
+        fn encode_state(&self, state: &i32) -> Result<Vec<u8>, JournalError> {
+            self.encode_action(state)
+        }
+
+        fn decode_state(&self, data: &[u8]) -> Result<i32, JournalError> {
+            self.decode_action(data)
+        }
+    }
+
+    fn test_reducer() -> ReducerFn<i32, i32, (), ()> {
+        |state, action| ReducerResult {
+            state: Rc::new(**state * 10 + action),
+            events: vec![],
+            effects: vec![],
+        }
+    }
+
+    #[test]
+    fn test_journal_replay() {
+        let log = MemoryLog::new();
+        let journal = Journal::new(TestCodec, log.clone());
+        let store = Store::new(test_reducer(), 0);
+        store.add_middleware(JournalMiddleware::new(journal.clone()));
+        store.dispatch(1);
+        store.dispatch(2);
+        store.dispatch(3);
+        assert_eq!(123, *store.state());
+
+        let records = log.records().unwrap();
+        assert_eq!(
+            vec![0, 1, 2],
+            records
+                .iter()
+                .map(|record| record.sequence)
+                .collect::<Vec<_>>()
+        );
+        assert!(records[0].timestamp <= records[2].timestamp);
+
+        let store = Store::replay(test_reducer(), 0, &journal).unwrap();
+        assert_eq!(123, *store.state());
+    }
+
+    /// Rejects negative actions.
+    struct PositiveReducer;
+
+    impl Reducer<i32, i32, (), ()> for PositiveReducer {
+        fn reduce(&self, state: &Rc<i32>, action: &i32) -> ReducerResult<i32, (), ()> {
+            test_reducer()(state, action)
+        }
+
+        fn try_reduce(
+            &self,
+            state: &Rc<i32>,
+            action: &i32,
+        ) -> Result<ReducerResult<i32, (), ()>, ReduceError> {
+            if *action < 0 {
+                Err(ReduceError::new("action cannot be negative"))
+            } else {
+                Ok(self.reduce(state, action))
+            }
+        }
+    }
+
+    #[test]
+    fn test_journal_rejected() {
+        let log = MemoryLog::new();
+        let journal = Journal::new(TestCodec, log.clone());
+        let store = Store::new_fallible(PositiveReducer, 0);
+        store.add_middleware(JournalMiddleware::new(journal.clone()).snapshot_every(3));
+        store.dispatch(1);
+        store.dispatch(-5);
+        store.dispatch(2);
+        assert_eq!(12, *store.state());
+
+        // The rejected action does not count towards the snapshot.
+        let kinds: Vec<_> = log
+            .records()
+            .unwrap()
+            .iter()
+            .map(|record| record.kind)
+            .collect();
+        assert_eq!(
+            vec![
+                JournalRecordKind::Action,
+                JournalRecordKind::Action,
+                JournalRecordKind::Rejected,
+                JournalRecordKind::Action,
+            ],
+            kinds
+        );
+        assert_eq!(1, log.records().unwrap()[2].sequence);
+
+        let store = Store::replay(PositiveReducer, 0, &journal).unwrap();
+        assert_eq!(12, *store.state());
+
+        // An action without a rejection marker (such as after a crash
+        // in the middle of a dispatch) is skipped if it is rejected
+        // again.
+        journal.append(&-7).unwrap();
+        journal.append(&3).unwrap();
+        let store = Store::replay(PositiveReducer, 0, &journal).unwrap();
+        assert_eq!(123, *store.state());
+        assert_eq!(5, journal.next_sequence().unwrap());
+    }
+
+    #[test]
+    fn test_journal_snapshot() {
+        let log = MemoryLog::new();
+        let journal = Journal::new(TestCodec, log.clone());
+        let store = Store::new(test_reducer(), 0);
+        store.add_middleware(JournalMiddleware::new(journal.clone()).snapshot_every(2));
+        for action in 1..=5 {
+            store.dispatch(action);
+        }
+        assert_eq!(12345, *store.state());
+
+        let records = log.records().unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(JournalRecordKind::Snapshot, records[0].kind);
+        assert_eq!(4, records[0].sequence);
+        assert_eq!(JournalRecordKind::Action, records[1].kind);
+        assert_eq!(4, records[1].sequence);
+
+        // The initial state is not used when there is a snapshot.
+        let store = Store::replay(test_reducer(), 9, &journal).unwrap();
+        assert_eq!(12345, *store.state());
+
+        // A new journal on the same log continues the sequence.
+        let journal = Journal::new(TestCodec, log);
+        assert_eq!(5, journal.next_sequence().unwrap());
+    }
+
+    #[test]
+    fn test_file_log() {
+        let path = std::env::temp_dir().join(format!(
+            "reactive-state-journal-test-{}.log",
+            std::process::id()
+        ));
+        let log = FileLog::new(&path);
+        assert!(log.records().unwrap().is_empty());
+
+        let journal = Journal::new(TestCodec, log.clone());
+        journal.append(&1).unwrap();
+        journal.append(&2).unwrap();
+        journal.snapshot(&12).unwrap();
+        journal.append(&3).unwrap();
+
+        let records = log.records().unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(JournalRecordKind::Snapshot, records[0].kind);
+        assert_eq!(3i32.to_le_bytes().to_vec(), records[1].data);
+
+        // An incomplete record at the end of the file is ignored.
+        let mut data = std::fs::read(&path).unwrap();
+        data.extend_from_slice(&[0, 3, 0]);
+        std::fs::write(&path, data).unwrap();
+
+        let store = Store::replay(test_reducer(), 0, &journal).unwrap();
+        assert_eq!(123, *store.state());
+
+        // It is removed before appending to the log again.
+        let journal = Journal::new(TestCodec, FileLog::new(&path));
+        journal.append(&4).unwrap();
+        let store = Store::replay(test_reducer(), 0, &journal).unwrap();
+        assert_eq!(1234, *store.state());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_file_log_record_too_large() {
+        assert_eq!(
+            Some(u32::MAX.to_le_bytes()),
+            FileLog::encode_len(u32::MAX as usize).ok()
+        );
+        let error = FileLog::encode_len(u32::MAX as usize + 1).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidInput, error.kind());
+    }
+}
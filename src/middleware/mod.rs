@@ -3,6 +3,18 @@
 //! middleware implementations which can be used as utilities in an
 //! application.
 
+pub mod async_effect;
+pub mod journal;
+pub mod time_travel;
+
+#[cfg(feature = "devtools")]
+#[cfg_attr(docsrs, doc(cfg(feature = "devtools")))]
+pub mod devtools;
+
+#[cfg(feature = "persist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "persist")))]
+pub mod persist;
+
 #[cfg(feature = "simple_logger")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simple_logger")))]
 pub mod simple_logger;
@@ -11,7 +23,40 @@ pub mod simple_logger;
 #[cfg_attr(docsrs, doc(cfg(feature = "web_logger")))]
 pub mod web_logger;
 
-use crate::Store;
+use crate::{ReduceError, Store};
+use std::{borrow::Borrow, rc::Rc};
+
+/// A unique identifier for [Middleware] which has been added to a
+/// [Store], returned by [Store::add_middleware()] and
+/// [Store::insert_middleware()].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MiddlewareId(u64);
+
+impl MiddlewareId {
+    pub(crate) fn new(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// The position in a [Store]'s middleware chain to insert
+/// [Middleware] using [Store::insert_middleware()]. Middleware
+/// earlier in the chain is invoked first, and invokes the middleware
+/// after it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MiddlewarePosition {
+    /// At the start of the chain, before all other middleware.
+    First,
+    /// At the end of the chain, after all other middleware.
+    Last,
+    /// Immediately before the middleware with the specified id. If
+    /// that middleware is no longer in the store, this behaves the
+    /// same as [MiddlewarePosition::Last].
+    Before(MiddlewareId),
+    /// Immediately after the middleware with the specified id. If
+    /// that middleware is no longer in the store, this behaves the
+    /// same as [MiddlewarePosition::Last].
+    After(MiddlewareId),
+}
 
 pub struct ReduceMiddlewareResult<Event, Effect> {
     pub events: Vec<Event>,
@@ -27,40 +72,128 @@ impl<Event, Effect> Default for ReduceMiddlewareResult<Event, Effect> {
     }
 }
 
-/// Executes subsequent middleware and then runs the [Reducer](crate::Reducer).
-pub type ReduceFn<State, Action, Event, Effect> = fn(
-    &Store<State, Action, Event, Effect>,
-    Option<&Action>,
-) -> ReduceMiddlewareResult<Event, Effect>;
+/// Executes subsequent middleware and then runs the
+/// [Reducer](crate::Reducer), see [Middleware::on_reduce()].
+///
+/// Each invocation of [Middleware::on_reduce()] is given its own
+/// `ReduceNext`, which records the position in the middleware chain,
+/// so the chain is unaffected by any other chains which are run
+/// before it is resumed. It can be invoked any number of times, for
+/// example to replace an action with several others.
+pub struct ReduceNext<'a, State, Action, Event, Effect> {
+    store: &'a Store<State, Action, Event, Effect>,
+    index: usize,
+}
+
+impl<'a, State, Action, Event, Effect> ReduceNext<'a, State, Action, Event, Effect> {
+    pub(crate) fn new(store: &'a Store<State, Action, Event, Effect>, index: usize) -> Self {
+        Self { store, index }
+    }
+
+    /// Execute the subsequent middleware with the `action`, and then
+    /// run the [Reducer](crate::Reducer). The `action` may be
+    /// borrowed (such as the action given to
+    /// [Middleware::on_reduce()]) or owned (such as a replacement
+    /// action created by the middleware). If `action` is `None`, the
+    /// reducer is not run (see [ReduceNext::reduce_none()]).
+    pub fn reduce<A: Borrow<Action>>(
+        &self,
+        action: Option<A>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        self.store
+            .middleware_reduce_next(self.index, action.as_ref().map(Borrow::borrow))
+    }
+
+    /// Execute the subsequent middleware without an action, so the
+    /// [Reducer](crate::Reducer) is not run. This is equivalent to
+    /// `reduce(None)`, without needing to name the action type.
+    pub fn reduce_none(&self) -> ReduceMiddlewareResult<Event, Effect> {
+        self.store.middleware_reduce_next(self.index, None)
+    }
+
+    /// Execute the subsequent middleware and then run the
+    /// [Reducer](crate::Reducer) with each of the `actions` in turn,
+    /// returning the events and effects produced by all of them.
+    pub fn reduce_all<A, I>(&self, actions: I) -> ReduceMiddlewareResult<Event, Effect>
+    where
+        A: Borrow<Action>,
+        I: IntoIterator<Item = A>,
+    {
+        let mut result = ReduceMiddlewareResult::default();
+        for action in actions {
+            let action_result = self.reduce(Some(action));
+            result.events.extend(action_result.events);
+            result.effects.extend(action_result.effects);
+        }
+        result
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Clone for ReduceNext<'a, State, Action, Event, Effect> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Copy for ReduceNext<'a, State, Action, Event, Effect> {}
+
+/// Executes subsequent middleware and then notifies the listeners,
+/// see [Middleware::on_notify()].
+///
+/// Each invocation of [Middleware::on_notify()] is given its own
+/// `NotifyNext`, which records the position in the middleware chain,
+/// so the chain is unaffected by any other chains which are run
+/// before it is resumed.
+pub struct NotifyNext<'a, State, Action, Event, Effect> {
+    store: &'a Store<State, Action, Event, Effect>,
+    index: usize,
+}
+
+impl<'a, State, Action, Event, Effect> NotifyNext<'a, State, Action, Event, Effect> {
+    pub(crate) fn new(store: &'a Store<State, Action, Event, Effect>, index: usize) -> Self {
+        Self { store, index }
+    }
+
+    /// Execute the subsequent middleware with the `events`, returning
+    /// the events which the listeners will be notified of.
+    pub fn notify(&self, events: Vec<Event>) -> Vec<Event> {
+        self.store.middleware_notify_next(self.index, events)
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Clone for NotifyNext<'a, State, Action, Event, Effect> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
 
-/// Executes subsequent middleware and then notifies the listeners.
-pub type NotifyFn<State, Action, Event, Effect> =
-    fn(&Store<State, Action, Event, Effect>, Vec<Event>) -> Vec<Event>;
+impl<'a, State, Action, Event, Effect> Copy for NotifyNext<'a, State, Action, Event, Effect> {}
 
 /// `Middleware` used to modify the behaviour of a [Store] during a
 /// [Store::dispatch()].
 pub trait Middleware<State, Action, Event, Effect> {
     /// This method is invoked by the [Store] during a
     /// [Store::dispatch()] just before the `Action` is sent to the
-    /// [Reducer](crate::Reducer). It is necessary to call the
-    /// provided `reduce` function, which executes subsequent
-    /// middleware and runs the [Reducer](crate::Reducer), and usually
-    /// the events produced by the `reduce` function are returned from
-    /// this method.
+    /// [Reducer](crate::Reducer). It is necessary to call
+    /// [ReduceNext::reduce()] on the provided `next`, which executes
+    /// subsequent middleware and runs the [Reducer](crate::Reducer),
+    /// and usually the events produced by it are returned from this
+    /// method.
     ///
-    /// This method allows modifying the action in question, or even
-    /// removing it, preventing the [Reducer](crate::Reducer) from
+    /// This method allows modifying the action in question, replacing
+    /// it with several actions (see [ReduceNext::reduce_all()]), or
+    /// even removing it, preventing the [Reducer](crate::Reducer) from
     /// processing the action. It also allows modifying the events
     /// produced by the [Reducer](crate::Reducer) before the
     /// [Middleware::on_notify()] is invoked and they are sent to the
     /// [Store] listeners.
     fn on_reduce(
         &self,
-        store: &Store<State, Action, Event, Effect>,
+        _store: &Store<State, Action, Event, Effect>,
         action: Option<&Action>,
-        reduce: ReduceFn<State, Action, Event, Effect>,
+        next: ReduceNext<'_, State, Action, Event, Effect>,
     ) -> ReduceMiddlewareResult<Event, Effect> {
-        reduce(store, action)
+        next.reduce(action)
     }
 
     /// Process an `Effect`. Returns `None` if the effect was
@@ -78,18 +211,34 @@ pub trait Middleware<State, Action, Event, Effect> {
     /// [Store::dispatch()] after the [Reducer](crate::Reducer) has
     /// processed the `Action` and all [Middleware::on_reduce()]
     /// methods have completed, just before resulting events are
-    /// sent to the store listeners. It is necessary to call the
-    /// provided `notify` function, which executes subsequent
-    /// middleware and then notifies the listeners.
+    /// sent to the store listeners. It is necessary to call
+    /// [NotifyNext::notify()] on the provided `next`, which executes
+    /// subsequent middleware and then notifies the listeners.
     ///
     /// This method allows modifying the events in question before the
     /// listeners are notified.
     fn on_notify(
         &self,
-        store: &Store<State, Action, Event, Effect>,
+        _store: &Store<State, Action, Event, Effect>,
         events: Vec<Event>,
-        notify: NotifyFn<State, Action, Event, Effect>,
+        next: NotifyNext<'_, State, Action, Event, Effect>,
     ) -> Vec<Event> {
-        notify(store, events)
+        next.notify(events)
     }
+
+    /// This method is invoked by a [Store] created with
+    /// [Store::new_fallible()] when its
+    /// [Reducer](crate::Reducer) rejects an `Action` during a
+    /// [Store::dispatch()]. The state has been left untouched, and
+    /// this is invoked on every middleware in the chain (in order)
+    /// before the store's error listeners are notified.
+    fn on_error(&self, _store: &Store<State, Action, Event, Effect>, _error: &ReduceError) {}
+
+    /// This method is invoked by the [Store] on every middleware in
+    /// the chain (in order) after its state has been replaced using
+    /// [Store::replace_state()], which bypasses the
+    /// [Reducer](crate::Reducer) and the other middleware methods.
+    /// The new `state` is the store's current state, and the
+    /// listeners have not been notified yet.
+    fn on_replace_state(&self, _store: &Store<State, Action, Event, Effect>, _state: &Rc<State>) {}
 }
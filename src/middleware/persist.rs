@@ -0,0 +1,719 @@
+//! [Middleware] which persists the state of a [Store] (or a subset of
+//! it) to a [PersistStorage] backend using [serde], so that it can be
+//! rehydrated when the application next starts.
+//!
+//! The state is serialized as JSON, inside an envelope which records
+//! the [Schema] version it was written with. When the persisted
+//! state is loaded using [load()] or [rehydrate()], states written
+//! with an older version are upgraded using the migration functions
+//! registered with the [Schema].
+//!
+//! Writes are debounced using
+//! [min_interval()](PersistMiddleware::min_interval()). There is no
+//! timer involved, a state which was not written because the interval
+//! had not elapsed is written after the next dispatch once it has, or
+//! when [flush()](PersistMiddleware::flush()) is called, or when the
+//! middleware is dropped.
+//!
+//! ## Example
+//!
+//! ```
+//! use reactive_state::{
+//!     middleware::persist::{rehydrate, MemoryStorage, PersistMiddleware, Schema},
+//!     ReducerFn, ReducerResult,
+//! };
+//! use std::rc::Rc;
+//!
+//! let reducer: ReducerFn<Vec<String>, String, (), ()> = |state, action| {
+//!     let mut new_state = Vec::clone(state);
+//!     new_state.push(action.clone());
+//!     ReducerResult {
+//!         state: Rc::new(new_state),
+//!         events: vec![()],
+//!         effects: vec![],
+//!     }
+//! };
+//!
+//! let storage = MemoryStorage::new();
+//!
+//! let store = rehydrate(reducer, &storage, &Schema::new(1), Vec::new).unwrap();
+//! store.add_middleware(PersistMiddleware::new(storage.clone()).version(1));
+//! store.dispatch("hello".to_string());
+//! drop(store);
+//!
+//! // The next time the application starts.
+//! let store = rehydrate(reducer, &storage, &Schema::new(1), Vec::new).unwrap();
+//! assert_eq!(vec!["hello".to_string()], *store.state());
+//! ```
+
+use super::{Middleware, NotifyNext};
+use crate::{Reducer, Store};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// An error which occurred while persisting or rehydrating a state.
+#[derive(Debug)]
+pub enum PersistError {
+    /// An error reading from or writing to the [PersistStorage].
+    Io(io::Error),
+    /// An error serializing or deserializing the state.
+    Serde(serde_json::Error),
+    /// The persisted data is not a valid envelope.
+    InvalidEnvelope,
+    /// The persisted state was written with a newer version of the
+    /// [Schema] than the one being used to load it.
+    UnsupportedVersion { found: u32, current: u32 },
+    /// There is no migration registered with the [Schema] to upgrade
+    /// a state from the specified version.
+    MissingMigration(u32),
+}
+
+impl Display for PersistError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistError::Io(error) => write!(f, "persisted state storage error: {}", error),
+            PersistError::Serde(error) => {
+                write!(f, "persisted state serialization error: {}", error)
+            }
+            PersistError::InvalidEnvelope => write!(f, "persisted state has an invalid envelope"),
+            PersistError::UnsupportedVersion { found, current } => write!(
+                f,
+                "persisted state version {} is newer than the current version {}",
+                found, current
+            ),
+            PersistError::MissingMigration(version) => write!(
+                f,
+                "no migration for persisted state from version {}",
+                version
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PersistError::Io(error) => Some(error),
+            PersistError::Serde(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PersistError {
+    fn from(error: io::Error) -> Self {
+        PersistError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(error: serde_json::Error) -> Self {
+        PersistError::Serde(error)
+    }
+}
+
+/// A backend which stores the serialized state for a
+/// [PersistMiddleware].
+pub trait PersistStorage {
+    /// Load the most recently saved data, or `None` if nothing has
+    /// been saved yet.
+    fn load(&self) -> io::Result<Option<String>>;
+    /// Save `data`, replacing anything which was previously saved.
+    fn save(&self, data: &str) -> io::Result<()>;
+}
+
+/// A [PersistStorage] which saves to a file on the file system.
+///
+/// The data is first written to a temporary file next to the
+/// destination, which is then renamed, so that the file is never left
+/// partially written.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    /// Create a new [FileStorage] which saves to the file at `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl PersistStorage for FileStorage {
+    fn load(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn save(&self, data: &str) -> io::Result<()> {
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, &self.path)
+    }
+}
+
+/// A [PersistStorage] which saves in memory, intended for use in
+/// tests. Clones of this storage share the same data.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    data: Rc<RefCell<Option<String>>>,
+}
+
+impl MemoryStorage {
+    /// Create a new empty [MemoryStorage].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The data which is currently saved.
+    pub fn data(&self) -> Option<String> {
+        self.data.borrow().clone()
+    }
+}
+
+impl PersistStorage for MemoryStorage {
+    fn load(&self) -> io::Result<Option<String>> {
+        Ok(self.data())
+    }
+
+    fn save(&self, data: &str) -> io::Result<()> {
+        *self.data.borrow_mut() = Some(data.to_string());
+        Ok(())
+    }
+}
+
+/// A function which upgrades a persisted state from one [Schema]
+/// version to the next.
+type MigrationFn = dyn Fn(Value) -> Value;
+
+/// The version of the persisted state, and the migrations used to
+/// upgrade states which were persisted with older versions.
+pub struct Schema {
+    version: u32,
+    migrations: BTreeMap<u32, Box<MigrationFn>>,
+}
+
+impl Schema {
+    /// Create a new [Schema] with the current `version`.
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// The current version of this schema.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Register a migration which upgrades a state persisted with
+    /// version `from` to version `from + 1`.
+    pub fn migration<F: Fn(Value) -> Value + 'static>(mut self, from: u32, migrate: F) -> Self {
+        self.migrations.insert(from, Box::new(migrate));
+        self
+    }
+
+    /// Wrap a serialized `state` in an envelope with the current
+    /// version.
+    fn wrap(&self, state: Value) -> Value {
+        let mut envelope = serde_json::Map::new();
+        envelope.insert("version".to_string(), Value::from(self.version));
+        envelope.insert("state".to_string(), state);
+        Value::Object(envelope)
+    }
+
+    /// Take the state out of an `envelope`, migrating it to the
+    /// current version.
+    fn unwrap(&self, envelope: Value) -> Result<Value, PersistError> {
+        let mut envelope = match envelope {
+            Value::Object(envelope) => envelope,
+            _ => return Err(PersistError::InvalidEnvelope),
+        };
+        let mut version = envelope
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or(PersistError::InvalidEnvelope)? as u32;
+        let mut state = envelope
+            .remove("state")
+            .ok_or(PersistError::InvalidEnvelope)?;
+
+        if version > self.version {
+            return Err(PersistError::UnsupportedVersion {
+                found: version,
+                current: self.version,
+            });
+        }
+
+        while version < self.version {
+            let migrate = self
+                .migrations
+                .get(&version)
+                .ok_or(PersistError::MissingMigration(version))?;
+            state = migrate(state);
+            version += 1;
+        }
+
+        Ok(state)
+    }
+}
+
+/// Load a persisted state (or subset of a state) from `storage`,
+/// migrating it to the current version of the `schema`. Returns
+/// `None` if nothing has been persisted yet.
+pub fn load<T, S>(storage: &S, schema: &Schema) -> Result<Option<T>, PersistError>
+where
+    T: DeserializeOwned,
+    S: PersistStorage + ?Sized,
+{
+    match storage.load()? {
+        Some(data) => {
+            let state = schema.unwrap(serde_json::from_str(&data)?)?;
+            Ok(Some(serde_json::from_value(state)?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Create a new [Store] using the state persisted in `storage` as
+/// its initial state, or the state produced by `default` if nothing
+/// has been persisted yet. This is the counterpart to a
+/// [PersistMiddleware] which persists the entire state.
+pub fn rehydrate<State, Action, Event, Effect, R, S, D>(
+    reducer: R,
+    storage: &S,
+    schema: &Schema,
+    default: D,
+) -> Result<Store<State, Action, Event, Effect>, PersistError>
+where
+    State: DeserializeOwned,
+    Event: Clone,
+    R: Reducer<State, Action, Event, Effect> + 'static,
+    S: PersistStorage + ?Sized,
+    D: FnOnce() -> State,
+{
+    let initial_state = load(storage, schema)?.unwrap_or_else(default);
+    Ok(Store::new(reducer, initial_state))
+}
+
+type SerializeFn<State> = dyn Fn(&State) -> Result<Value, serde_json::Error>;
+type ErrorFn = dyn Fn(&PersistError);
+
+struct PersistInner<State, S: PersistStorage> {
+    storage: S,
+    schema: Schema,
+    serialize: Box<SerializeFn<State>>,
+    on_error: Option<Box<ErrorFn>>,
+    min_interval: Duration,
+    last_write: Option<Instant>,
+    /// The most recent state, which has not been written yet.
+    pending: Option<Rc<State>>,
+    /// The most recent state which was written, used to avoid writing
+    /// the same state more than once.
+    written: Option<Rc<State>>,
+}
+
+impl<State, S: PersistStorage> PersistInner<State, S> {
+    /// Record the store's current `state`, and write it if
+    /// [PersistMiddleware::min_interval()] has elapsed since the last
+    /// write.
+    fn update(&mut self, state: Rc<State>) {
+        let unchanged = self
+            .written
+            .as_ref()
+            .map(|written| Rc::ptr_eq(written, &state))
+            .unwrap_or(false);
+        self.pending = if unchanged { None } else { Some(state) };
+
+        let elapsed = self
+            .last_write
+            .map(|last_write| last_write.elapsed() >= self.min_interval)
+            .unwrap_or(true);
+        if elapsed {
+            let result = self.write_pending();
+            self.report(result);
+        }
+    }
+
+    /// Write the pending state. If this fails, the state remains
+    /// pending, and the write is retried after the next dispatch.
+    fn write_pending(&mut self) -> Result<(), PersistError> {
+        let state = match &self.pending {
+            Some(state) => state.clone(),
+            None => return Ok(()),
+        };
+        self.last_write = Some(Instant::now());
+
+        let envelope = self.schema.wrap((self.serialize)(&state)?);
+        self.storage.save(&serde_json::to_string(&envelope)?)?;
+        self.pending = None;
+        self.written = Some(state);
+        Ok(())
+    }
+
+    fn report(&self, result: Result<(), PersistError>) {
+        if let (Err(error), Some(on_error)) = (result, &self.on_error) {
+            on_error(&error);
+        }
+    }
+}
+
+impl<State, S: PersistStorage> Drop for PersistInner<State, S> {
+    fn drop(&mut self) {
+        let result = self.write_pending();
+        self.report(result);
+    }
+}
+
+/// [Middleware] which persists the state of a [Store] to a
+/// [PersistStorage] after each dispatch.
+///
+/// This middleware is a cheap handle to shared persistence state, so
+/// a clone can be added to the store while another is kept to call
+/// [flush()](PersistMiddleware::flush()).
+///
+/// See [persist](super::persist) for more details.
+pub struct PersistMiddleware<State, S: PersistStorage> {
+    inner: Rc<RefCell<PersistInner<State, S>>>,
+}
+
+impl<State, S> PersistMiddleware<State, S>
+where
+    State: Serialize + 'static,
+    S: PersistStorage,
+{
+    /// Create a new [PersistMiddleware] which persists the entire
+    /// state to `storage`, with version `0` of the [Schema].
+    pub fn new(storage: S) -> Self {
+        Self::new_select(storage, |state: &State| serde_json::to_value(state))
+    }
+}
+
+impl<State, S> PersistMiddleware<State, S>
+where
+    S: PersistStorage,
+{
+    fn new_select(
+        storage: S,
+        serialize: impl Fn(&State) -> Result<Value, serde_json::Error> + 'static,
+    ) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(PersistInner {
+                storage,
+                schema: Schema::new(0),
+                serialize: Box::new(serialize),
+                on_error: None,
+                min_interval: Duration::from_secs(0),
+                last_write: None,
+                pending: None,
+                written: None,
+            })),
+        }
+    }
+
+    /// Create a new [PersistMiddleware] which persists the subset of
+    /// the state produced by `select` to `storage`.
+    pub fn select<T, F>(storage: S, select: F) -> Self
+    where
+        T: Serialize,
+        F: Fn(&State) -> T + 'static,
+    {
+        Self::new_select(storage, move |state: &State| {
+            serde_json::to_value(select(state))
+        })
+    }
+
+    /// Set the [Schema] version which the persisted state is written
+    /// with. A [Schema] with the same version should be used with
+    /// [load()] or [rehydrate()]. By default this is `0`.
+    pub fn version(self, version: u32) -> Self {
+        self.inner.borrow_mut().schema = Schema::new(version);
+        self
+    }
+
+    /// Set the minimum amount of time between writes to the storage.
+    /// By default the state is written after every dispatch which
+    /// changes it.
+    pub fn min_interval(self, min_interval: Duration) -> Self {
+        self.inner.borrow_mut().min_interval = min_interval;
+        self
+    }
+
+    /// Set a function to be called when an error occurs while
+    /// writing the state during a dispatch (or when the middleware is
+    /// dropped). By default these errors are ignored.
+    pub fn on_error<F: Fn(&PersistError) + 'static>(self, on_error: F) -> Self {
+        self.inner.borrow_mut().on_error = Some(Box::new(on_error));
+        self
+    }
+
+    /// Returns `true` if there is a state which has not been written
+    /// to the storage yet.
+    pub fn is_pending(&self) -> bool {
+        self.inner.borrow().pending.is_some()
+    }
+
+    /// Write any state which has not been written to the storage yet,
+    /// regardless of [min_interval()](PersistMiddleware::min_interval()).
+    pub fn flush(&self) -> Result<(), PersistError> {
+        self.inner.borrow_mut().write_pending()
+    }
+}
+
+impl<State, S: PersistStorage> Clone for PersistMiddleware<State, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<State, Action, Event, Effect, S> Middleware<State, Action, Event, Effect>
+    for PersistMiddleware<State, S>
+where
+    Event: Clone,
+    S: PersistStorage,
+{
+    fn on_notify(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        events: Vec<Event>,
+        next: NotifyNext<'_, State, Action, Event, Effect>,
+    ) -> Vec<Event> {
+        self.inner.borrow_mut().update(store.state());
+        next.notify(events)
+    }
+
+    fn on_replace_state(&self, _store: &Store<State, Action, Event, Effect>, state: &Rc<State>) {
+        self.inner.borrow_mut().update(state.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        load, rehydrate, FileStorage, MemoryStorage, PersistError, PersistMiddleware,
+        PersistStorage, Schema,
+    };
+    use crate::{ReducerFn, ReducerResult, Store};
+    use serde_json::Value;
+    use std::{cell::Cell, collections::BTreeMap, io, rc::Rc, time::Duration};
+
+    type TestState = BTreeMap<String, u32>;
+
+    fn test_reducer() -> ReducerFn<TestState, (&'static str, u32), (), ()> {
+        |state, (key, value)| {
+            let mut new_state = state.as_ref().clone();
+            new_state.insert(key.to_string(), *value);
+            ReducerResult {
+                state: Rc::new(new_state),
+                events: vec![],
+                effects: vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn test_persist() {
+        let storage = MemoryStorage::new();
+        let store = Store::new(test_reducer(), TestState::new());
+        store.add_middleware(PersistMiddleware::new(storage.clone()).version(3));
+        assert_eq!(None, storage.data());
+
+        store.dispatch(("a", 1));
+        let data: Value = serde_json::from_str(&storage.data().unwrap()).unwrap();
+        assert_eq!(serde_json::json!({"state": {"a": 1}, "version": 3}), data);
+
+        let state: TestState = load(&storage, &Schema::new(3)).unwrap().unwrap();
+        assert_eq!(Some(&1), state.get("a"));
+    }
+
+    #[test]
+    fn test_persist_select() {
+        let storage = MemoryStorage::new();
+        let store = Store::new(test_reducer(), TestState::new());
+        store.add_middleware(PersistMiddleware::select(
+            storage.clone(),
+            |state: &TestState| state.get("a").copied(),
+        ));
+
+        store.dispatch(("b", 2));
+        assert_eq!(
+            None,
+            load::<Option<u32>, _>(&storage, &Schema::new(0))
+                .unwrap()
+                .unwrap()
+        );
+        store.dispatch(("a", 1));
+        assert_eq!(
+            Some(1),
+            load::<Option<u32>, _>(&storage, &Schema::new(0))
+                .unwrap()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_debounce() {
+        let storage = MemoryStorage::new();
+        let store = Store::new(test_reducer(), TestState::new());
+        let persist =
+            PersistMiddleware::new(storage.clone()).min_interval(Duration::from_secs(3600));
+        store.add_middleware(persist.clone());
+
+        store.dispatch(("a", 1));
+        store.dispatch(("a", 2));
+        assert!(persist.is_pending());
+        let state: TestState = load(&storage, &Schema::new(0)).unwrap().unwrap();
+        assert_eq!(Some(&1), state.get("a"));
+
+        persist.flush().unwrap();
+        assert!(!persist.is_pending());
+        let state: TestState = load(&storage, &Schema::new(0)).unwrap().unwrap();
+        assert_eq!(Some(&2), state.get("a"));
+
+        // Pending states are written when the middleware is dropped.
+        store.dispatch(("a", 3));
+        drop(persist);
+        drop(store);
+        let state: TestState = load(&storage, &Schema::new(0)).unwrap().unwrap();
+        assert_eq!(Some(&3), state.get("a"));
+    }
+
+    #[test]
+    fn test_persist_replace_state_and_transaction() {
+        let storage = MemoryStorage::new();
+        let store = Store::new(test_reducer(), TestState::new());
+        store.add_middleware(PersistMiddleware::new(storage.clone()));
+
+        let mut state = TestState::new();
+        state.insert("a".to_string(), 1);
+        store.replace_state(Rc::new(state), vec![]);
+        let state: TestState = load(&storage, &Schema::new(0)).unwrap().unwrap();
+        assert_eq!(Some(&1), state.get("a"));
+
+        store
+            .transaction(|transaction| {
+                transaction.dispatch(("b", 2));
+                Ok::<(), ()>(())
+            })
+            .unwrap();
+        let state: TestState = load(&storage, &Schema::new(0)).unwrap().unwrap();
+        assert_eq!(Some(&2), state.get("b"));
+    }
+
+    /// A [PersistStorage] which fails to save while `fail` is set.
+    #[derive(Clone, Default)]
+    struct FailingStorage {
+        storage: MemoryStorage,
+        fail: Rc<Cell<bool>>,
+    }
+
+    impl PersistStorage for FailingStorage {
+        fn load(&self) -> io::Result<Option<String>> {
+            self.storage.load()
+        }
+
+        fn save(&self, data: &str) -> io::Result<()> {
+            if self.fail.get() {
+                return Err(io::Error::other("save failed"));
+            }
+            self.storage.save(data)
+        }
+    }
+
+    #[test]
+    fn test_retry_failed_write() {
+        let storage = FailingStorage::default();
+        let store = Store::new(test_reducer(), TestState::new());
+        let errors = Rc::new(Cell::new(0));
+        let errors_local = errors.clone();
+        let persist = PersistMiddleware::new(storage.clone())
+            .on_error(move |_| errors_local.set(errors_local.get() + 1));
+        store.add_middleware(persist.clone());
+
+        storage.fail.set(true);
+        store.dispatch(("a", 1));
+        assert_eq!(1, errors.get());
+        assert!(persist.is_pending());
+        assert_eq!(None, storage.storage.data());
+
+        storage.fail.set(false);
+        persist.flush().unwrap();
+        assert!(!persist.is_pending());
+        let state: TestState = load(&storage, &Schema::new(0)).unwrap().unwrap();
+        assert_eq!(Some(&1), state.get("a"));
+    }
+
+    #[test]
+    fn test_migration() {
+        let storage = MemoryStorage::new();
+        storage
+            .save(r#"{"version":0,"state":{"count":1}}"#)
+            .unwrap();
+
+        let schema = Schema::new(2)
+            .migration(0, |mut state| {
+                // Rename "count" to "a".
+                let object = state.as_object_mut().unwrap();
+                let count = object.remove("count").unwrap();
+                object.insert("a".to_string(), count);
+                state
+            })
+            .migration(1, |mut state| {
+                state["b"] = Value::from(2);
+                state
+            });
+
+        let state: TestState = load(&storage, &schema).unwrap().unwrap();
+        assert_eq!(Some(&1), state.get("a"));
+        assert_eq!(Some(&2), state.get("b"));
+
+        match load::<TestState, _>(&storage, &Schema::new(1)) {
+            Err(PersistError::MissingMigration(0)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        storage.save(r#"{"version":3,"state":{}}"#).unwrap();
+        match load::<TestState, _>(&storage, &schema) {
+            Err(PersistError::UnsupportedVersion {
+                found: 3,
+                current: 2,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_file_storage_rehydrate() {
+        let path = std::env::temp_dir().join(format!(
+            "reactive-state-persist-test-{}.json",
+            std::process::id()
+        ));
+        let storage = FileStorage::new(&path);
+        assert_eq!(None, storage.load().unwrap());
+
+        let store = rehydrate(test_reducer(), &storage, &Schema::new(0), TestState::new).unwrap();
+        store.add_middleware(PersistMiddleware::new(storage.clone()));
+        store.dispatch(("a", 1));
+        drop(store);
+
+        let store = rehydrate(test_reducer(), &storage, &Schema::new(0), TestState::new).unwrap();
+        assert_eq!(Some(&1), store.state().get("a"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -4,13 +4,15 @@
 
 use super::ReduceMiddlewareResult;
 use crate::{
-    middleware::{Middleware, ReduceFn},
+    middleware::{Middleware, ReduceNext},
     Store,
 };
-use std::{fmt::Debug, hash::Hash};
+use std::fmt::Debug;
 
+#[derive(Default)]
 pub enum LogLevel {
     Trace,
+    #[default]
     Debug,
     Warn,
     Info,
@@ -27,12 +29,6 @@ impl LogLevel {
     }
 }
 
-impl Default for LogLevel {
-    fn default() -> Self {
-        LogLevel::Debug
-    }
-}
-
 /// Logging [Middleware](crate::middleware::Middleware) which uses the
 /// [log](log) macros to publish actions/events that occur within the
 /// [Store](crate::Store).
@@ -64,7 +60,7 @@ impl Default for SimpleLoggerMiddleware {
 impl<State, Action, Event, Effect> Middleware<State, Action, Event, Effect>
     for SimpleLoggerMiddleware
 where
-    Event: Clone + Hash + Eq + Debug,
+    Event: Clone + Debug,
     State: Debug,
     Action: Debug,
     Effect: Debug,
@@ -73,7 +69,7 @@ where
         &self,
         store: &Store<State, Action, Event, Effect>,
         action: Option<&Action>,
-        reduce: ReduceFn<State, Action, Event, Effect>,
+        next: ReduceNext<'_, State, Action, Event, Effect>,
     ) -> ReduceMiddlewareResult<Event, Effect> {
         let was_action = match &action {
             Some(action) => {
@@ -88,7 +84,7 @@ where
             }
         };
 
-        let events = reduce(store, action);
+        let events = next.reduce(action);
 
         if was_action {
             self.log_level
@@ -109,14 +105,14 @@ where
 
     fn on_notify(
         &self,
-        store: &Store<State, Action, Event, Effect>,
+        _store: &Store<State, Action, Event, Effect>,
         events: Vec<Event>,
-        notify: super::NotifyFn<State, Action, Event, Effect>,
+        next: super::NotifyNext<'_, State, Action, Event, Effect>,
     ) -> Vec<Event> {
         for event in &events {
             self.log_level.log(format!("event: {:?}", event));
         }
 
-        notify(store, events)
+        next.notify(events)
     }
 }
@@ -0,0 +1,618 @@
+//! [Middleware] which records the history of actions dispatched to a
+//! [Store], for time-travel debugging.
+//!
+//! Every `Action` which is reduced is recorded along with the
+//! resulting `State`, events and effects. The store can then be moved
+//! to any point in this history with
+//! [jump_to()](TimeTravelMiddleware::jump_to()),
+//! [step_back()](TimeTravelMiddleware::step_back()) and
+//! [step_forward()](TimeTravelMiddleware::step_forward()), and
+//! individual actions can be skipped using
+//! [toggle_action()](TimeTravelMiddleware::toggle_action()), which
+//! re-computes the rest of the history using the store's
+//! [Reducer](crate::Reducer).
+//!
+//! Moving through the history replaces the store's state using
+//! [Store::replace_state()], which notifies the store listeners, but
+//! does not invoke any middleware or process any effects.
+//!
+//! ## Example
+//!
+//! ```
+//! use reactive_state::{middleware::time_travel::TimeTravelMiddleware, ReducerFn, ReducerResult, Store};
+//! use std::rc::Rc;
+//!
+//! let reducer: ReducerFn<i32, i32, (), ()> = |state, action| ReducerResult {
+//!     state: Rc::new(**state + action),
+//!     events: vec![()],
+//!     effects: vec![],
+//! };
+//!
+//! let store = Store::new(reducer, 0);
+//! let time_travel = TimeTravelMiddleware::new();
+//! store.add_middleware(time_travel.clone());
+//!
+//! store.dispatch(1);
+//! store.dispatch(10);
+//! store.dispatch(100);
+//! assert_eq!(111, *store.state());
+//!
+//! time_travel.step_back(&store);
+//! assert_eq!(11, *store.state());
+//!
+//! // Skip the action which added 10.
+//! time_travel.toggle_action(&store, 1);
+//! assert_eq!(1, *store.state());
+//!
+//! time_travel.jump_to(&store, 3);
+//! assert_eq!(101, *store.state());
+//! ```
+
+use super::{Middleware, ReduceMiddlewareResult, ReduceNext};
+use crate::Store;
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+/// An `Action` recorded by the [TimeTravelMiddleware], along with the
+/// result of reducing it.
+pub struct TimeTravelEntry<State, Action, Event, Effect> {
+    /// The action which was dispatched.
+    pub action: Action,
+    /// The state after the action was reduced. If the action is
+    /// skipped, this is the same as the state before the action.
+    pub state: Rc<State>,
+    /// The events produced when the action was reduced.
+    pub events: Vec<Event>,
+    /// The effects produced when the action was reduced.
+    pub effects: Vec<Effect>,
+    /// Whether this action is currently being skipped, see
+    /// [TimeTravelMiddleware::toggle_action()].
+    pub skipped: bool,
+}
+
+impl<State, Action, Event, Effect> Clone for TimeTravelEntry<State, Action, Event, Effect>
+where
+    Action: Clone,
+    Event: Clone,
+    Effect: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            action: self.action.clone(),
+            state: self.state.clone(),
+            events: self.events.clone(),
+            effects: self.effects.clone(),
+            skipped: self.skipped,
+        }
+    }
+}
+
+impl<State, Action, Event, Effect> Debug for TimeTravelEntry<State, Action, Event, Effect>
+where
+    State: Debug,
+    Action: Debug,
+    Event: Debug,
+    Effect: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeTravelEntry")
+            .field("action", &self.action)
+            .field("state", &self.state)
+            .field("events", &self.events)
+            .field("effects", &self.effects)
+            .field("skipped", &self.skipped)
+            .finish()
+    }
+}
+
+struct TimeTravelHistory<State, Action, Event, Effect> {
+    /// The state before the first recorded action.
+    initial: Option<Rc<State>>,
+    entries: Vec<TimeTravelEntry<State, Action, Event, Effect>>,
+    /// The number of entries which have been applied to the store's
+    /// current state.
+    position: usize,
+}
+
+impl<State, Action, Event, Effect> TimeTravelHistory<State, Action, Event, Effect>
+where
+    Event: Clone,
+{
+    /// The state after the first `index` entries have been applied.
+    fn state_at(&self, index: usize) -> Option<Rc<State>> {
+        if index == 0 {
+            self.initial.clone()
+        } else {
+            self.entries.get(index - 1).map(|entry| entry.state.clone())
+        }
+    }
+
+    /// The events produced by the entries which are not skipped
+    /// between the `from` and `to` positions (in either direction), in
+    /// order.
+    fn events_between(&self, from: usize, to: usize, events: &mut Vec<Event>) {
+        let range = from.min(to)..from.max(to);
+        for entry in &self.entries[range] {
+            if !entry.skipped {
+                events.extend(entry.events.iter().cloned());
+            }
+        }
+    }
+}
+
+/// [Middleware] which records the history of actions dispatched to a
+/// [Store], for time-travel debugging.
+///
+/// This middleware is a cheap handle to a shared history, so a clone
+/// can be added to the store while another is kept to control it.
+///
+/// See [time_travel](super::time_travel) for more details.
+pub struct TimeTravelMiddleware<State, Action, Event, Effect> {
+    history: Rc<RefCell<TimeTravelHistory<State, Action, Event, Effect>>>,
+}
+
+impl<State, Action, Event, Effect> TimeTravelMiddleware<State, Action, Event, Effect>
+where
+    Action: Clone,
+    Event: Clone,
+    Effect: Clone,
+{
+    /// Create a new [TimeTravelMiddleware] with an empty history. The
+    /// initial state is taken from the store when the first action
+    /// is recorded.
+    pub fn new() -> Self {
+        Self {
+            history: Rc::new(RefCell::new(TimeTravelHistory {
+                initial: None,
+                entries: Vec::new(),
+                position: 0,
+            })),
+        }
+    }
+
+    /// The number of actions which have been recorded.
+    pub fn len(&self) -> usize {
+        self.history.borrow().entries.len()
+    }
+
+    /// Returns `true` if no actions have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.history.borrow().entries.is_empty()
+    }
+
+    /// The current position in the history, which is the number of
+    /// recorded actions which have been applied to the store's state.
+    /// This is equal to [len()](TimeTravelMiddleware::len()) unless
+    /// the store has been moved back in time.
+    pub fn position(&self) -> usize {
+        self.history.borrow().position
+    }
+
+    /// A copy of the recorded actions, in the order that they were
+    /// dispatched.
+    pub fn entries(&self) -> Vec<TimeTravelEntry<State, Action, Event, Effect>> {
+        self.history.borrow().entries.clone()
+    }
+
+    /// A copy of the most recently recorded action, or `None` if no
+    /// actions have been recorded.
+    pub fn last_entry(&self) -> Option<TimeTravelEntry<State, Action, Event, Effect>> {
+        self.history.borrow().entries.last().cloned()
+    }
+
+    /// The state of the store at the specified position in the
+    /// history, or `None` if it is out of bounds.
+    pub fn state_at(&self, index: usize) -> Option<Rc<State>> {
+        self.history.borrow().state_at(index)
+    }
+
+    /// Move the `store` to the specified position in the history,
+    /// where `0` is the state before the first recorded action, and
+    /// [len()](TimeTravelMiddleware::len()) is the state after the
+    /// most recent one. The store listeners are notified of the
+    /// events produced by the actions between the current position
+    /// and the new one.
+    ///
+    /// Returns `false` (and does nothing) if `index` is out of
+    /// bounds.
+    ///
+    /// If an action is dispatched while the store has been moved back
+    /// in time, the actions after the current position are discarded.
+    pub fn jump_to(&self, store: &Store<State, Action, Event, Effect>, index: usize) -> bool {
+        let (state, events) = {
+            let mut history = self.history.borrow_mut();
+            let state = match history.state_at(index) {
+                Some(state) => state,
+                None => return false,
+            };
+
+            let mut events = Vec::new();
+            history.events_between(history.position, index, &mut events);
+            history.position = index;
+            (state, events)
+        };
+
+        store.replace_state(state, events);
+        true
+    }
+
+    /// Move the `store` back by one action, returns `false` if it is
+    /// already at the start of the history.
+    pub fn step_back(&self, store: &Store<State, Action, Event, Effect>) -> bool {
+        match self.position().checked_sub(1) {
+            Some(index) => self.jump_to(store, index),
+            None => false,
+        }
+    }
+
+    /// Move the `store` forward by one action, returns `false` if it
+    /// is already at the end of the history.
+    pub fn step_forward(&self, store: &Store<State, Action, Event, Effect>) -> bool {
+        self.jump_to(store, self.position() + 1)
+    }
+
+    /// Toggle whether the recorded action at the specified `index` is
+    /// skipped, and re-compute the states of the actions after it
+    /// using the `store`'s [Reducer](crate::Reducer). Effects produced
+    /// during the re-computation are recorded, but not processed.
+    ///
+    /// If the toggled action is before the current position, the
+    /// store's state is replaced with the re-computed one, and the
+    /// listeners are notified of the events produced by the affected
+    /// actions.
+    ///
+    /// Returns `false` (and does nothing) if `index` is out of
+    /// bounds.
+    pub fn toggle_action(&self, store: &Store<State, Action, Event, Effect>, index: usize) -> bool {
+        let replace = {
+            let mut history = self.history.borrow_mut();
+            if index >= history.entries.len() {
+                return false;
+            }
+
+            let position = history.position;
+            let mut events = Vec::new();
+            history.events_between(index, position, &mut events);
+
+            let entry = &mut history.entries[index];
+            entry.skipped = !entry.skipped;
+
+            let mut state = history
+                .state_at(index)
+                .expect("initial state recorded with the first action");
+            let reducer = store.reducer();
+            for entry in &mut history.entries[index..] {
+                if entry.skipped {
+                    entry.state = state.clone();
+                } else {
+                    let result = reducer.reduce(&state, &entry.action);
+                    entry.state = result.state;
+                    entry.events = result.events;
+                    entry.effects = result.effects;
+                    state = entry.state.clone();
+                }
+            }
+
+            if index < position {
+                // Include the events of the toggled action whether it
+                // was skipped or not.
+                events.extend(history.entries[index].events.iter().cloned());
+                history.events_between(index, position, &mut events);
+                history.state_at(position).map(|state| (state, events))
+            } else {
+                None
+            }
+        };
+
+        if let Some((state, events)) = replace {
+            store.replace_state(state, events);
+        }
+        true
+    }
+
+    /// Replace the recorded history with `actions` (each paired with
+    /// whether it is skipped), starting from `initial_state`, and
+    /// re-compute the states using the `store`'s
+    /// [Reducer](crate::Reducer). Effects produced during the
+    /// re-computation are recorded, but not processed.
+    ///
+    /// The store's state is replaced with the state after the last
+    /// action, and the listeners are notified of the events produced
+    /// by the actions which are not skipped.
+    pub fn import(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        initial_state: Rc<State>,
+        actions: Vec<(Action, bool)>,
+    ) {
+        let (state, events) = {
+            let mut history = self.history.borrow_mut();
+            let reducer = store.reducer();
+            let mut state = initial_state.clone();
+            history.entries = actions
+                .into_iter()
+                .map(|(action, skipped)| {
+                    if skipped {
+                        TimeTravelEntry {
+                            action,
+                            state: state.clone(),
+                            events: Vec::new(),
+                            effects: Vec::new(),
+                            skipped,
+                        }
+                    } else {
+                        let result = reducer.reduce(&state, &action);
+                        state = result.state.clone();
+                        TimeTravelEntry {
+                            action,
+                            state: result.state,
+                            events: result.events,
+                            effects: result.effects,
+                            skipped,
+                        }
+                    }
+                })
+                .collect();
+            history.initial = Some(initial_state);
+            history.position = history.entries.len();
+
+            let mut events = Vec::new();
+            history.events_between(0, history.position, &mut events);
+            (state, events)
+        };
+
+        store.replace_state(state, events);
+    }
+}
+
+impl<State, Action, Event, Effect> Default for TimeTravelMiddleware<State, Action, Event, Effect>
+where
+    Action: Clone,
+    Event: Clone,
+    Effect: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State, Action, Event, Effect> Clone for TimeTravelMiddleware<State, Action, Event, Effect> {
+    fn clone(&self) -> Self {
+        Self {
+            history: self.history.clone(),
+        }
+    }
+}
+
+impl<State, Action, Event, Effect> Middleware<State, Action, Event, Effect>
+    for TimeTravelMiddleware<State, Action, Event, Effect>
+where
+    Action: Clone,
+    Event: Clone,
+    Effect: Clone,
+{
+    fn on_reduce(
+        &self,
+        store: &Store<State, Action, Event, Effect>,
+        action: Option<&Action>,
+        next: ReduceNext<'_, State, Action, Event, Effect>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        if self.history.borrow().initial.is_none() {
+            self.history.borrow_mut().initial = Some(store.state());
+        }
+
+        let result = next.reduce(action);
+
+        // Actions rejected by the reducer did not change the state.
+        if let (Some(action), false) = (action, store.reduce_rejected()) {
+            let mut history = self.history.borrow_mut();
+            // Dispatching while the store has been moved back in time
+            // discards the actions after the current position.
+            let position = history.position;
+            history.entries.truncate(position);
+            history.entries.push(TimeTravelEntry {
+                action: action.clone(),
+                state: store.state(),
+                events: result.events.clone(),
+                effects: result.effects.clone(),
+                skipped: false,
+            });
+            history.position += 1;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeTravelMiddleware;
+    use crate::{
+        Callback, ReduceError, Reducer, ReducerFn, ReducerResult, Store, TransactionError,
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    enum TestEvent {
+        Added(i32),
+    }
+
+    fn test_reducer() -> ReducerFn<i32, i32, TestEvent, i32> {
+        |state, action| ReducerResult {
+            state: Rc::new(**state + action),
+            events: vec![TestEvent::Added(*action)],
+            effects: vec![*action],
+        }
+    }
+
+    type TestStore = Store<i32, i32, TestEvent, i32>;
+    type TestTimeTravel = TimeTravelMiddleware<i32, i32, TestEvent, i32>;
+
+    fn test_store() -> (TestStore, TestTimeTravel) {
+        let store = Store::new(test_reducer(), 0);
+        let time_travel = TimeTravelMiddleware::new();
+        store.add_middleware(time_travel.clone());
+        for action in &[1, 10, 100] {
+            store.dispatch(*action);
+        }
+        (store, time_travel)
+    }
+
+    #[test]
+    fn test_record() {
+        let (store, time_travel) = test_store();
+        assert_eq!(3, time_travel.len());
+        assert_eq!(3, time_travel.position());
+
+        let entries = time_travel.entries();
+        assert_eq!(10, entries[1].action);
+        assert_eq!(11, *entries[1].state);
+        assert_eq!(vec![TestEvent::Added(10)], entries[1].events);
+        assert_eq!(vec![10], entries[1].effects);
+        assert_eq!(
+            Some(100),
+            time_travel.last_entry().map(|entry| entry.action)
+        );
+        assert_eq!(Some(0), time_travel.state_at(0).map(|state| *state));
+        assert_eq!(111, *store.state());
+    }
+
+    #[test]
+    fn test_jump_to() {
+        let (store, time_travel) = test_store();
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events_local = events.clone();
+        let callback = Callback::new(move |_: Rc<i32>, event: Option<TestEvent>| {
+            events_local.borrow_mut().push(event.unwrap());
+        });
+        let _subscription = store.subscribe_events(
+            &callback,
+            vec![
+                TestEvent::Added(1),
+                TestEvent::Added(10),
+                TestEvent::Added(100),
+            ],
+        );
+
+        assert!(time_travel.jump_to(&store, 1));
+        assert_eq!(1, *store.state());
+        assert_eq!(1, time_travel.position());
+        assert_eq!(
+            vec![TestEvent::Added(10), TestEvent::Added(100)],
+            *events.borrow()
+        );
+
+        assert!(time_travel.step_forward(&store));
+        assert_eq!(11, *store.state());
+        assert!(time_travel.step_back(&store));
+        assert!(time_travel.step_back(&store));
+        assert_eq!(0, *store.state());
+        assert!(!time_travel.step_back(&store));
+        assert!(!time_travel.jump_to(&store, 4));
+        assert_eq!(0, *store.state());
+    }
+
+    #[test]
+    fn test_dispatch_after_jump() {
+        let (store, time_travel) = test_store();
+        time_travel.jump_to(&store, 1);
+        store.dispatch(5);
+
+        assert_eq!(6, *store.state());
+        assert_eq!(2, time_travel.len());
+        assert_eq!(2, time_travel.position());
+        assert!(!time_travel.step_forward(&store));
+    }
+
+    #[test]
+    fn test_toggle_action() {
+        let (store, time_travel) = test_store();
+
+        assert!(time_travel.toggle_action(&store, 1));
+        assert_eq!(101, *store.state());
+        let entries = time_travel.entries();
+        assert!(entries[1].skipped);
+        assert_eq!(1, *entries[1].state);
+        assert_eq!(101, *entries[2].state);
+
+        assert!(time_travel.toggle_action(&store, 1));
+        assert_eq!(111, *store.state());
+        assert!(!time_travel.toggle_action(&store, 3));
+    }
+
+    #[test]
+    fn test_toggle_action_after_position() {
+        let (store, time_travel) = test_store();
+        time_travel.jump_to(&store, 1);
+
+        time_travel.toggle_action(&store, 1);
+        assert_eq!(1, *store.state());
+        assert_eq!(Some(101), time_travel.state_at(3).map(|state| *state));
+    }
+
+    /// Rejects actions which would make the state negative.
+    struct NonNegativeReducer;
+
+    impl Reducer<i32, i32, TestEvent, i32> for NonNegativeReducer {
+        fn reduce(&self, state: &Rc<i32>, action: &i32) -> ReducerResult<i32, TestEvent, i32> {
+            test_reducer()(state, action)
+        }
+
+        fn try_reduce(
+            &self,
+            state: &Rc<i32>,
+            action: &i32,
+        ) -> Result<ReducerResult<i32, TestEvent, i32>, ReduceError> {
+            if **state + action < 0 {
+                Err(ReduceError::new("state cannot be negative"))
+            } else {
+                Ok(self.reduce(state, action))
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejected_transaction_not_recorded() {
+        let store = Store::new(NonNegativeReducer, 3);
+        let time_travel = TimeTravelMiddleware::new();
+        store.add_middleware(time_travel.clone());
+
+        // The commit is reduced again on top of the action queued
+        // ahead of it, and rejected on its second action.
+        store.batch(|store| {
+            store.dispatch(-2);
+            let result: Result<(), TransactionError<()>> = store.transaction(|tx| {
+                assert!(tx.dispatch(-1));
+                assert!(tx.dispatch(-2));
+                Ok(())
+            });
+            assert!(result.is_ok());
+        });
+
+        assert_eq!(1, *store.state());
+        assert_eq!(1, time_travel.len());
+        assert_eq!(Some(-2), time_travel.last_entry().map(|entry| entry.action));
+    }
+
+    #[test]
+    fn test_import() {
+        let (store, time_travel) = test_store();
+
+        time_travel.import(
+            &store,
+            Rc::new(5),
+            vec![(2, false), (20, true), (200, false)],
+        );
+        assert_eq!(207, *store.state());
+        assert_eq!(3, time_travel.len());
+        assert_eq!(3, time_travel.position());
+
+        let entries = time_travel.entries();
+        assert!(entries[1].skipped);
+        assert_eq!(7, *entries[1].state);
+        assert_eq!(Some(5), time_travel.state_at(0).map(|state| *state));
+
+        assert!(time_travel.toggle_action(&store, 1));
+        assert_eq!(227, *store.state());
+    }
+}
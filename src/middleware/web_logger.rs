@@ -5,7 +5,7 @@
 
 use super::{Middleware, ReduceMiddlewareResult};
 use serde::Serialize;
-use std::{fmt::Display, hash::Hash};
+use std::fmt::Display;
 use wasm_bindgen::JsValue;
 use web_sys::console;
 
@@ -120,12 +120,12 @@ impl WebLoggerMiddleware {
         &self,
         store: &crate::Store<State, Action, Event, Effect>,
         action: Option<&Action>,
-        reduce: super::ReduceFn<State, Action, Event, Effect>,
+        next: super::ReduceNext<'_, State, Action, Event, Effect>,
     ) -> ReduceMiddlewareResult<Event, Effect>
     where
         State: Serialize,
         Action: Serialize + Display,
-        Event: Clone + Hash + Eq + Serialize,
+        Event: Clone + Serialize,
         Effect: Serialize,
     {
         let prev_state_js = JsValue::from_serde(&(*store.state())).unwrap();
@@ -136,7 +136,7 @@ impl WebLoggerMiddleware {
             None => "None".to_string(),
         };
 
-        let result = reduce(store, action);
+        let result = next.reduce(action);
         let next_state_js = JsValue::from_serde(&(*store.state())).unwrap();
 
         let effects_js = JsValue::from_serde(&result.effects).unwrap();
@@ -209,12 +209,12 @@ impl WebLoggerMiddleware {
         &self,
         store: &crate::Store<State, Action, Event, Effect>,
         action: Option<&Action>,
-        reduce: super::ReduceFn<State, Action, Event, Effect>,
+        next: super::ReduceNext<'_, State, Action, Event, Effect>,
     ) -> ReduceMiddlewareResult<Event, Effect>
     where
         State: Serialize,
         Action: Serialize + Display,
-        Event: Clone + Hash + Eq + Serialize,
+        Event: Clone + Serialize,
         Effect: Serialize,
     {
         let action_display = format!(
@@ -229,7 +229,7 @@ impl WebLoggerMiddleware {
 
         let prev_state = store.state();
 
-        let result = reduce(store, action);
+        let result = next.reduce(action);
         let next_state = store.state();
 
         let log_object = OnReduceLog {
@@ -247,9 +247,9 @@ impl WebLoggerMiddleware {
 
     fn on_notify_groups<State, Action, Event, Effect>(
         &self,
-        store: &crate::Store<State, Action, Event, Effect>,
+        _store: &crate::Store<State, Action, Event, Effect>,
         events: Vec<Event>,
-        notify: super::NotifyFn<State, Action, Event, Effect>,
+        next: super::NotifyNext<'_, State, Action, Event, Effect>,
     ) -> Vec<Event>
     where
         Event: Serialize,
@@ -277,17 +277,17 @@ impl WebLoggerMiddleware {
             console::group_end();
         }
 
-        notify(store, events)
+        next.notify(events)
     }
 
     fn on_notify_no_groups<State, Action, Event, Effect>(
         &self,
         store: &crate::Store<State, Action, Event, Effect>,
         events: Vec<Event>,
-        notify: super::NotifyFn<State, Action, Event, Effect>,
+        next: super::NotifyNext<'_, State, Action, Event, Effect>,
     ) -> Vec<Event>
     where
-        Event: Serialize + Clone + Hash + Eq,
+        Event: Serialize + Clone,
         State: Serialize,
     {
         let log_object = OnNotifyLog {
@@ -301,7 +301,7 @@ impl WebLoggerMiddleware {
 
         self.log_level.log(vec![display, log_object_js]);
 
-        notify(store, events)
+        next.notify(events)
     }
 }
 
@@ -315,18 +315,18 @@ impl<State, Action, Event, Effect> Middleware<State, Action, Event, Effect> for
 where
     State: Serialize,
     Action: Serialize + Display,
-    Event: Clone + Hash + Eq + Serialize,
+    Event: Clone + Serialize,
     Effect: Serialize,
 {
     fn on_reduce(
         &self,
         store: &crate::Store<State, Action, Event, Effect>,
         action: Option<&Action>,
-        reduce: super::ReduceFn<State, Action, Event, Effect>,
+        next: super::ReduceNext<'_, State, Action, Event, Effect>,
     ) -> ReduceMiddlewareResult<Event, Effect> {
         match self.display_type {
-            DisplayType::Groups => self.on_reduce_groups(store, action, reduce),
-            DisplayType::SingleObject => self.on_reduce_no_groups(store, action, reduce),
+            DisplayType::Groups => self.on_reduce_groups(store, action, next),
+            DisplayType::SingleObject => self.on_reduce_no_groups(store, action, next),
         }
     }
 
@@ -342,11 +342,11 @@ where
         &self,
         store: &crate::Store<State, Action, Event, Effect>,
         events: Vec<Event>,
-        notify: super::NotifyFn<State, Action, Event, Effect>,
+        next: super::NotifyNext<'_, State, Action, Event, Effect>,
     ) -> Vec<Event> {
         match self.display_type {
-            DisplayType::Groups => self.on_notify_groups(store, events, notify),
-            DisplayType::SingleObject => self.on_notify_no_groups(store, events, notify),
+            DisplayType::Groups => self.on_notify_groups(store, events, next),
+            DisplayType::SingleObject => self.on_notify_no_groups(store, events, next),
         }
     }
 }
@@ -0,0 +1,43 @@
+use std::any::Any;
+
+/// Where a panic caught by a [Store](crate::Store) occurred, see
+/// [Store::set_panic_handler()](crate::Store::set_panic_handler()).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PanicSource {
+    /// In the [Reducer](crate::Reducer) or
+    /// [Middleware](crate::middleware::Middleware) while an item from
+    /// the dispatch queue was being processed. The store's state has
+    /// been restored to what it was before the item was processed.
+    Dispatch,
+    /// In a listener's callback while it was being notified. The
+    /// remaining listeners are still notified.
+    Listener,
+    /// In a middleware's
+    /// [Middleware::on_error()](crate::middleware::Middleware::on_error())
+    /// while it was being told about a rejected action. The remaining
+    /// middleware and error listeners are still told about it.
+    Middleware,
+}
+
+/// A panic which was caught by a [Store](crate::Store), see
+/// [Store::set_panic_handler()](crate::Store::set_panic_handler()).
+#[derive(Debug)]
+pub struct StorePanic {
+    /// Where the panic occurred.
+    pub source: PanicSource,
+    /// The panic message, if the panic payload was a string.
+    pub message: Option<String>,
+}
+
+impl StorePanic {
+    pub(crate) fn new(source: PanicSource, payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => Some(*message),
+            Err(payload) => payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string()),
+        };
+
+        Self { source, message }
+    }
+}
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{error::Error, fmt::Display, marker::PhantomData, rc::Rc};
 
 /// A wrapper for a function that implements the [Reducer](Reducer)
 /// trait.
@@ -124,6 +124,140 @@ pub trait Reducer<State, Action, Event, Effect> {
         prev_state: &Rc<State>,
         action: &Action,
     ) -> ReducerResult<State, Event, Effect>;
+
+    /// Reduce an `Action` dispatched within a
+    /// [Store::transaction()](crate::Store::transaction()), or to a
+    /// store created with
+    /// [Store::new_fallible()](crate::Store::new_fallible()), in the
+    /// same manner as [reduce()](Reducer::reduce()). Returning an
+    /// error rejects the action: it rolls back the entire
+    /// transaction, or leaves the store's state untouched.
+    ///
+    /// By default this calls [reduce()](Reducer::reduce()), and never
+    /// rejects an action.
+    fn try_reduce(
+        &self,
+        prev_state: &Rc<State>,
+        action: &Action,
+    ) -> Result<ReducerResult<State, Event, Effect>, ReduceError> {
+        Ok(self.reduce(prev_state, action))
+    }
+
+    /// Whether this reducer rejects actions using
+    /// [try_reduce()](Reducer::try_reduce()), in which case a store
+    /// created with [Store::new()](crate::Store::new()) reduces every
+    /// action with [try_reduce()](Reducer::try_reduce()), as if it
+    /// was created with
+    /// [Store::new_fallible()](crate::Store::new_fallible()).
+    ///
+    /// By default this returns `false`. Reducers composed of other
+    /// reducers return `true` if any of them do.
+    fn is_fallible(&self) -> bool {
+        false
+    }
+}
+
+/// A wrapper for a function that implements the [Reducer] trait,
+/// which can reject an `Action` by returning an error, see
+/// [Reducer::try_reduce()].
+///
+/// This is a fallible reducer (see [Reducer::is_fallible()]), so a
+/// [Store](crate::Store) always reduces it with
+/// [Reducer::try_reduce()], and its rejections are reported. Only when
+/// [Reducer::reduce()] is called directly (for example to re-compute
+/// states outside of a dispatch) does a rejected action silently
+/// leave the state unchanged.
+///
+/// ## Example
+///
+/// ```
+/// use reactive_state::{ReducerResult, Store, TryReducerFn};
+/// use std::rc::Rc;
+///
+/// let reducer: TryReducerFn<u32, u32, (), (), &str> = TryReducerFn(|state, action| {
+///     let state = state.checked_sub(*action).ok_or("not enough")?;
+///     Ok(ReducerResult {
+///         state: Rc::new(state),
+///         events: vec![()],
+///         effects: vec![],
+///     })
+/// });
+///
+/// let store = Store::new_fallible(reducer, 1);
+/// assert!(store.dispatch(2u32).error.is_some());
+/// assert_eq!(1, *store.state());
+/// ```
+#[allow(clippy::type_complexity)]
+pub struct TryReducerFn<State, Action, Event, Effect, E>(
+    pub fn(&Rc<State>, &Action) -> Result<ReducerResult<State, Event, Effect>, E>,
+);
+
+impl<State, Action, Event, Effect, E> Clone for TryReducerFn<State, Action, Event, Effect, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<State, Action, Event, Effect, E> Copy for TryReducerFn<State, Action, Event, Effect, E> {}
+
+impl<State, Action, Event, Effect, E> Reducer<State, Action, Event, Effect>
+    for TryReducerFn<State, Action, Event, Effect, E>
+where
+    E: Into<Box<dyn Error>>,
+{
+    fn reduce(
+        &self,
+        prev_state: &Rc<State>,
+        action: &Action,
+    ) -> ReducerResult<State, Event, Effect> {
+        (self.0)(prev_state, action).unwrap_or_else(|_| ReducerResult {
+            state: prev_state.clone(),
+            events: Vec::new(),
+            effects: Vec::new(),
+        })
+    }
+
+    fn try_reduce(
+        &self,
+        prev_state: &Rc<State>,
+        action: &Action,
+    ) -> Result<ReducerResult<State, Event, Effect>, ReduceError> {
+        (self.0)(prev_state, action).map_err(ReduceError::new)
+    }
+
+    fn is_fallible(&self) -> bool {
+        true
+    }
+}
+
+/// An error returned by [Reducer::try_reduce()] to reject an
+/// `Action`.
+#[derive(Debug)]
+pub struct ReduceError(Box<dyn Error>);
+
+impl ReduceError {
+    /// Create a new [ReduceError] with the reason that the action was
+    /// rejected.
+    pub fn new<E: Into<Box<dyn Error>>>(error: E) -> Self {
+        Self(error.into())
+    }
+
+    /// The reason that the action was rejected, if it is of type `E`.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+}
+
+impl Display for ReduceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "action rejected by reducer: {}", self.0)
+    }
+}
+
+impl Error for ReduceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
 }
 
 /// The result of a [Reducer::reduce()] function.
@@ -193,25 +327,326 @@ impl<State, Action, Event, Effect> Reducer<State, Action, Event, Effect>
 
         sum_result
     }
+
+    fn try_reduce(
+        &self,
+        prev_state: &Rc<State>,
+        action: &Action,
+    ) -> Result<ReducerResult<State, Event, Effect>, ReduceError> {
+        let mut sum_result: ReducerResult<State, Event, Effect> = ReducerResult {
+            state: prev_state.clone(),
+            events: Vec::new(),
+            effects: Vec::new(),
+        };
+
+        for reducer in &self.reducers {
+            let result = reducer.try_reduce(&sum_result.state, action)?;
+            sum_result.state = result.state;
+            sum_result.events.extend(result.events);
+            sum_result.effects.extend(result.effects);
+        }
+
+        Ok(sum_result)
+    }
+
+    fn is_fallible(&self) -> bool {
+        self.reducers.iter().any(|reducer| reducer.is_fallible())
+    }
+}
+
+/// A field of the `State` reduced by a [CombinedReducer].
+trait FieldReducer<State, Action, Event, Effect> {
+    /// Reduce this field of `prev_state`. If the reducer replaced the
+    /// field's state, it is set on `next_state` (which is cloned from
+    /// `prev_state` the first time a field changes).
+    fn reduce_field(
+        &self,
+        prev_state: &State,
+        next_state: &mut Option<State>,
+        action: &Action,
+        events: &mut Vec<Event>,
+        effects: &mut Vec<Effect>,
+    );
+
+    /// Reduce this field of `prev_state` in the same way as
+    /// [FieldReducer::reduce_field()], using the field's
+    /// [Reducer::try_reduce()].
+    fn try_reduce_field(
+        &self,
+        prev_state: &State,
+        next_state: &mut Option<State>,
+        action: &Action,
+        events: &mut Vec<Event>,
+        effects: &mut Vec<Effect>,
+    ) -> Result<(), ReduceError>;
+
+    /// Whether the field's reducer is fallible, see
+    /// [Reducer::is_fallible()].
+    fn is_fallible(&self) -> bool;
+}
+
+struct Field<SubState, R, G, S> {
+    reducer: R,
+    get: G,
+    set: S,
+    phantom: PhantomData<SubState>,
+}
+
+impl<SubState, R, G, S> Field<SubState, R, G, S> {
+    /// Merge the `result` of reducing this field into `next_state`
+    /// and the combined `events` and `effects`.
+    fn apply<State, Event, Effect>(
+        &self,
+        prev_state: &State,
+        next_state: &mut Option<State>,
+        result: ReducerResult<SubState, Event, Effect>,
+        events: &mut Vec<Event>,
+        effects: &mut Vec<Effect>,
+    ) where
+        State: Clone,
+        G: Fn(&State) -> &Rc<SubState>,
+        S: Fn(&mut State, Rc<SubState>),
+    {
+        if !Rc::ptr_eq((self.get)(prev_state), &result.state) {
+            let next_state = next_state.get_or_insert_with(|| prev_state.clone());
+            (self.set)(next_state, result.state);
+        }
+
+        events.extend(result.events);
+        effects.extend(result.effects);
+    }
+}
+
+impl<State, SubState, Action, Event, Effect, R, G, S> FieldReducer<State, Action, Event, Effect>
+    for Field<SubState, R, G, S>
+where
+    State: Clone,
+    R: Reducer<SubState, Action, Event, Effect>,
+    G: Fn(&State) -> &Rc<SubState>,
+    S: Fn(&mut State, Rc<SubState>),
+{
+    fn reduce_field(
+        &self,
+        prev_state: &State,
+        next_state: &mut Option<State>,
+        action: &Action,
+        events: &mut Vec<Event>,
+        effects: &mut Vec<Effect>,
+    ) {
+        let result = self.reducer.reduce((self.get)(prev_state), action);
+        self.apply(prev_state, next_state, result, events, effects);
+    }
+
+    fn try_reduce_field(
+        &self,
+        prev_state: &State,
+        next_state: &mut Option<State>,
+        action: &Action,
+        events: &mut Vec<Event>,
+        effects: &mut Vec<Effect>,
+    ) -> Result<(), ReduceError> {
+        let result = self.reducer.try_reduce((self.get)(prev_state), action)?;
+        self.apply(prev_state, next_state, result, events, effects);
+        Ok(())
+    }
+
+    fn is_fallible(&self) -> bool {
+        self.reducer.is_fallible()
+    }
+}
+
+/// A [Reducer] composed of reducers for individual fields of the
+/// `State`, similar to `combineReducers()` in redux, which allows a
+/// large state to be split across independent modules.
+///
+/// Each field is an `Rc<SubState>` within the `State`, with its own
+/// [Reducer] which is given every `Action`, and accessed using a
+/// pair of lens functions. All the field reducers are run against
+/// the previous state, and the parent state is only rebuilt (by
+/// cloning it and setting the changed fields) when a field reducer
+/// returns a different `Rc` for its field. The events and effects
+/// produced by the field reducers are merged in the order that the
+/// fields were added.
+///
+/// ## Example
+///
+/// ```
+/// use reactive_state::{CombinedReducer, Reducer, ReducerFn, ReducerResult};
+/// use std::rc::Rc;
+///
+/// #[derive(Clone)]
+/// struct AppState {
+///     clicks: Rc<u32>,
+///     title: Rc<String>,
+/// }
+///
+/// enum AppAction {
+///     Click,
+///     SetTitle(String),
+/// }
+///
+/// let clicks: ReducerFn<u32, AppAction, &str, ()> = |state, action| match action {
+///     AppAction::Click => ReducerResult {
+///         state: Rc::new(**state + 1),
+///         events: vec!["clicked"],
+///         effects: vec![],
+///     },
+///     _ => ReducerResult {
+///         state: state.clone(),
+///         events: vec![],
+///         effects: vec![],
+///     },
+/// };
+///
+/// let title: ReducerFn<String, AppAction, &str, ()> = |state, action| match action {
+///     AppAction::SetTitle(title) => ReducerResult {
+///         state: Rc::new(title.clone()),
+///         events: vec!["title changed"],
+///         effects: vec![],
+///     },
+///     _ => ReducerResult {
+///         state: state.clone(),
+///         events: vec![],
+///         effects: vec![],
+///     },
+/// };
+///
+/// let reducer = CombinedReducer::new()
+///     .field(|state: &AppState| &state.clicks, |state, clicks| state.clicks = clicks, clicks)
+///     .field(|state: &AppState| &state.title, |state, title| state.title = title, title);
+///
+/// let state = Rc::new(AppState {
+///     clicks: Rc::new(0),
+///     title: Rc::new(String::new()),
+/// });
+///
+/// let result = reducer.reduce(&state, &AppAction::Click);
+/// assert_eq!(1, *result.state.clicks);
+/// assert!(Rc::ptr_eq(&state.title, &result.state.title));
+/// assert_eq!(vec!["clicked"], result.events);
+/// ```
+#[doc(alias = "combine_reducers")]
+pub struct CombinedReducer<State, Action, Event, Effect> {
+    fields: Vec<Box<dyn FieldReducer<State, Action, Event, Effect>>>,
+}
+
+impl<State, Action, Event, Effect> CombinedReducer<State, Action, Event, Effect> {
+    /// Create a new [CombinedReducer] without any fields.
+    pub fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    /// Add a field of the `State`, reduced by `reducer`. The `get`
+    /// function returns the field from the state, and the `set`
+    /// function replaces it.
+    pub fn field<SubState, R, G, S>(mut self, get: G, set: S, reducer: R) -> Self
+    where
+        State: Clone + 'static,
+        SubState: 'static,
+        Action: 'static,
+        Event: 'static,
+        Effect: 'static,
+        R: Reducer<SubState, Action, Event, Effect> + 'static,
+        G: Fn(&State) -> &Rc<SubState> + 'static,
+        S: Fn(&mut State, Rc<SubState>) + 'static,
+    {
+        self.fields.push(Box::new(Field {
+            reducer,
+            get,
+            set,
+            phantom: PhantomData,
+        }));
+        self
+    }
+
+    /// Build the combined result, where `next_state` is the rebuilt
+    /// state if any of the fields changed.
+    fn combined_result(
+        prev_state: &Rc<State>,
+        next_state: Option<State>,
+        events: Vec<Event>,
+        effects: Vec<Effect>,
+    ) -> ReducerResult<State, Event, Effect> {
+        ReducerResult {
+            state: next_state
+                .map(Rc::new)
+                .unwrap_or_else(|| prev_state.clone()),
+            events,
+            effects,
+        }
+    }
+}
+
+impl<State, Action, Event, Effect> Default for CombinedReducer<State, Action, Event, Effect> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State, Action, Event, Effect> Reducer<State, Action, Event, Effect>
+    for CombinedReducer<State, Action, Event, Effect>
+{
+    fn reduce(
+        &self,
+        prev_state: &Rc<State>,
+        action: &Action,
+    ) -> ReducerResult<State, Event, Effect> {
+        let mut next_state = None;
+        let mut events = Vec::new();
+        let mut effects = Vec::new();
+
+        for field in &self.fields {
+            field.reduce_field(
+                prev_state,
+                &mut next_state,
+                action,
+                &mut events,
+                &mut effects,
+            );
+        }
+
+        Self::combined_result(prev_state, next_state, events, effects)
+    }
+
+    fn try_reduce(
+        &self,
+        prev_state: &Rc<State>,
+        action: &Action,
+    ) -> Result<ReducerResult<State, Event, Effect>, ReduceError> {
+        let mut next_state = None;
+        let mut events = Vec::new();
+        let mut effects = Vec::new();
+
+        for field in &self.fields {
+            field.try_reduce_field(
+                prev_state,
+                &mut next_state,
+                action,
+                &mut events,
+                &mut effects,
+            )?;
+        }
+
+        Ok(Self::combined_result(
+            prev_state, next_state, events, effects,
+        ))
+    }
+
+    fn is_fallible(&self) -> bool {
+        self.fields.iter().any(|field| field.is_fallible())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{CompositeReducer, Reducer, ReducerResult};
+    use crate::{CombinedReducer, CompositeReducer, ReduceError, Reducer, ReducerResult};
     use std::rc::Rc;
 
+    #[derive(Default)]
     struct TestState {
         emitted_events: Vec<TestEvent>,
     }
 
-    impl Default for TestState {
-        fn default() -> Self {
-            TestState {
-                emitted_events: Vec::new(),
-            }
-        }
-    }
-
     struct TestAction;
 
     #[derive(Debug, Clone, PartialEq)]
@@ -281,4 +716,117 @@ mod tests {
             vec![TestEffect::Effect1, TestEffect::Effect2]
         );
     }
+
+    #[derive(Clone)]
+    struct CombinedState {
+        counter: Rc<i32>,
+        emitted_events: Rc<TestState>,
+    }
+
+    /// Adds the action to the counter, rejecting it in
+    /// [Reducer::try_reduce()] if the counter would become negative.
+    struct CounterReducer;
+
+    impl Reducer<i32, i32, TestEvent, TestEffect> for CounterReducer {
+        fn reduce(
+            &self,
+            prev_state: &Rc<i32>,
+            action: &i32,
+        ) -> ReducerResult<i32, TestEvent, TestEffect> {
+            if *action == 0 {
+                return ReducerResult {
+                    state: prev_state.clone(),
+                    events: vec![],
+                    effects: vec![],
+                };
+            }
+
+            ReducerResult {
+                state: Rc::new(**prev_state + action),
+                events: vec![TestEvent::Event2],
+                effects: vec![TestEffect::Effect2],
+            }
+        }
+
+        fn try_reduce(
+            &self,
+            prev_state: &Rc<i32>,
+            action: &i32,
+        ) -> Result<ReducerResult<i32, TestEvent, TestEffect>, ReduceError> {
+            if **prev_state + action < 0 {
+                return Err(ReduceError::new("negative"));
+            }
+            Ok(self.reduce(prev_state, action))
+        }
+    }
+
+    /// Leaves the state untouched.
+    struct NoopReducer;
+
+    impl Reducer<TestState, i32, TestEvent, TestEffect> for NoopReducer {
+        fn reduce(
+            &self,
+            prev_state: &Rc<TestState>,
+            _action: &i32,
+        ) -> ReducerResult<TestState, TestEvent, TestEffect> {
+            ReducerResult {
+                state: prev_state.clone(),
+                events: vec![TestEvent::Event1],
+                effects: vec![TestEffect::Effect1],
+            }
+        }
+    }
+
+    #[test]
+    fn combined_reducer() {
+        let reducer = CombinedReducer::new()
+            .field(
+                |state: &CombinedState| &state.emitted_events,
+                |state, emitted_events| state.emitted_events = emitted_events,
+                NoopReducer,
+            )
+            .field(
+                |state: &CombinedState| &state.counter,
+                |state, counter| state.counter = counter,
+                CounterReducer,
+            );
+
+        let state = Rc::new(CombinedState {
+            counter: Rc::new(0),
+            emitted_events: Rc::new(TestState::default()),
+        });
+
+        let result = reducer.reduce(&state, &0);
+        assert!(Rc::ptr_eq(&state, &result.state));
+        assert_eq!(vec![TestEvent::Event1], result.events);
+
+        let result = reducer.reduce(&state, &2);
+        assert!(!Rc::ptr_eq(&state, &result.state));
+        assert_eq!(2, *result.state.counter);
+        assert!(Rc::ptr_eq(
+            &state.emitted_events,
+            &result.state.emitted_events
+        ));
+        assert_eq!(vec![TestEvent::Event1, TestEvent::Event2], result.events);
+        assert_eq!(
+            vec![TestEffect::Effect1, TestEffect::Effect2],
+            result.effects
+        );
+
+        assert!(reducer.try_reduce(&state, &1).is_ok());
+        assert_eq!(
+            "action rejected by reducer: negative",
+            reducer.try_reduce(&state, &-1).err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn reduce_error_downcast() {
+        let error = ReduceError::new(std::fmt::Error);
+        assert_eq!(
+            Some(&std::fmt::Error),
+            error.downcast_ref::<std::fmt::Error>()
+        );
+        assert!(error.downcast_ref::<std::io::Error>().is_none());
+    }
 }
@@ -0,0 +1,199 @@
+use crate::{DispatchReport, StoreRef, Subscription};
+use std::rc::Rc;
+
+/// A view of a part of a [Store](crate::Store)'s `State`, created
+/// with [StoreRef::scope()].
+///
+/// The `SubState` is an `Rc<SubState>` field of the store's state,
+/// selected using a lens function, and `SubAction`s dispatched to
+/// this view are mapped into the store's `Action`s. This allows a
+/// feature module to be written against its own state and action
+/// types, while the application keeps a single [Store](crate::Store).
+///
+/// The view holds a [StoreRef] to the store, so it can be cloned and
+/// handed to components which outlive the scope it was created in.
+///
+/// ## Example
+///
+/// ```
+/// use reactive_state::{ReducerFn, ReducerResult, StoreRef};
+/// use std::rc::Rc;
+///
+/// #[derive(Clone, Default)]
+/// struct AppState {
+///     clicks: u32,
+///     title: Rc<String>,
+/// }
+///
+/// enum AppAction {
+///     Click,
+///     SetTitle(String),
+/// }
+///
+/// let reducer: ReducerFn<AppState, AppAction, (), ()> = |state, action| {
+///     let mut state = (**state).clone();
+///     match action {
+///         AppAction::Click => state.clicks += 1,
+///         AppAction::SetTitle(title) => state.title = Rc::new(title.clone()),
+///     }
+///     ReducerResult {
+///         state: Rc::new(state),
+///         events: vec![()],
+///         effects: vec![],
+///     }
+/// };
+///
+/// let store = StoreRef::new(reducer, AppState::default());
+/// let title = store.scope(|state: &AppState| &state.title, AppAction::SetTitle);
+///
+/// title.dispatch("Hello".to_string());
+/// assert_eq!("Hello", *title.state());
+/// ```
+pub struct ScopedStore<State, Action, Event, Effect, SubState, SubAction> {
+    store: StoreRef<State, Action, Event, Effect>,
+    #[allow(clippy::type_complexity)]
+    lens: Rc<dyn Fn(&State) -> &Rc<SubState>>,
+    action_map: Rc<dyn Fn(SubAction) -> Action>,
+}
+
+impl<State, Action, Event, Effect, SubState, SubAction>
+    ScopedStore<State, Action, Event, Effect, SubState, SubAction>
+where
+    Event: Clone,
+{
+    pub(crate) fn new<L, M>(
+        store: StoreRef<State, Action, Event, Effect>,
+        lens: L,
+        action_map: M,
+    ) -> Self
+    where
+        L: Fn(&State) -> &Rc<SubState> + 'static,
+        M: Fn(SubAction) -> Action + 'static,
+    {
+        Self {
+            store,
+            lens: Rc::new(lens),
+            action_map: Rc::new(action_map),
+        }
+    }
+
+    /// The [StoreRef] to the store which this is a view of.
+    pub fn store(&self) -> &StoreRef<State, Action, Event, Effect> {
+        &self.store
+    }
+
+    /// Get the part of the store's current `State` which this view
+    /// is scoped to. This is the `Rc` held by the store's state, so
+    /// it is not cloned.
+    pub fn state(&self) -> Rc<SubState> {
+        (self.lens)(&self.store.state()).clone()
+    }
+
+    /// Map the `action` into the store's `Action`, and dispatch it to
+    /// the store, see [Store::dispatch()].
+    pub fn dispatch<A: Into<SubAction>>(&self, action: A) -> DispatchReport<Event, Effect> {
+        self.store.dispatch((self.action_map)(action.into()))
+    }
+
+    /// Subscribe to changes in the part of the store's state which
+    /// this view is scoped to. The `callback` is invoked with the new
+    /// `SubState` only when it changes (compared using [PartialEq])
+    /// after the store listeners are notified, see
+    /// [Store::subscribe_selector()](crate::Store::subscribe_selector()).
+    ///
+    /// The store holds onto the `callback` until the returned
+    /// [Subscription] is dropped.
+    pub fn subscribe<C>(&self, callback: C) -> Subscription
+    where
+        State: 'static,
+        Event: 'static,
+        SubState: PartialEq + 'static,
+        C: Fn(Rc<SubState>) + 'static,
+    {
+        let lens = self.lens.clone();
+        self.store.subscribe_selector(
+            move |state: &State| lens(state).clone(),
+            move |_, sub_state: &Rc<SubState>| callback(sub_state.clone()),
+        )
+    }
+}
+
+impl<State, Action, Event, Effect, SubState, SubAction> Clone
+    for ScopedStore<State, Action, Event, Effect, SubState, SubAction>
+{
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            lens: self.lens.clone(),
+            action_map: self.action_map.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ReducerFn, ReducerResult, StoreRef};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Clone, Default)]
+    struct AppState {
+        clicks: u32,
+        title: Rc<String>,
+    }
+
+    enum AppAction {
+        Click,
+        SetTitle(String),
+    }
+
+    fn reducer() -> ReducerFn<AppState, AppAction, (), ()> {
+        |state, action| {
+            let mut state = (**state).clone();
+            match action {
+                AppAction::Click => state.clicks += 1,
+                AppAction::SetTitle(title) => state.title = Rc::new(title.clone()),
+            }
+            ReducerResult {
+                state: Rc::new(state),
+                events: vec![()],
+                effects: vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn test_scope() {
+        let store = StoreRef::new(reducer(), AppState::default());
+        let title = store.scope(|state: &AppState| &state.title, AppAction::SetTitle);
+
+        let titles = Rc::new(RefCell::new(Vec::new()));
+        let titles_copy = titles.clone();
+        let _subscription = title.subscribe(move |title: Rc<String>| {
+            titles_copy.borrow_mut().push((*title).clone());
+        });
+
+        let state = title.state();
+        assert_eq!("", *state);
+        assert!(Rc::ptr_eq(&state, &store.state().title));
+
+        store.dispatch(AppAction::Click);
+        assert_eq!(1, store.state().clicks);
+        assert!(titles.borrow().is_empty());
+
+        title.dispatch("Hello".to_string());
+        assert_eq!("Hello", *title.state());
+        assert_eq!(vec!["Hello".to_string()], *titles.borrow());
+
+        let clone = title.clone();
+        clone.dispatch("World".to_string());
+        assert_eq!("World", *title.state());
+        assert_eq!(1, store.state().clicks);
+        assert_eq!(2, titles.borrow().len());
+
+        // The view holds its own reference to the store.
+        drop(store);
+        title.dispatch("Again".to_string());
+        assert_eq!("Again", *clone.state());
+        assert_eq!(3, titles.borrow().len());
+    }
+}
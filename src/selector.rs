@@ -0,0 +1,120 @@
+use std::{
+    cell::RefCell,
+    fmt::Debug,
+    rc::{Rc, Weak},
+};
+
+/// The most recent state/output pair computed by a [Selector].
+struct SelectorCache<State, Output> {
+    /// A weak reference to the state, so that the cache does not keep
+    /// old states alive. Holding the weak reference also keeps the
+    /// allocation alive, so a new state can never share its address.
+    state: Weak<State>,
+    output: Output,
+}
+
+/// A function which computes a value derived from a
+/// [Store](crate::Store)'s `State`, memoizing the result against the
+/// most recent state that it was computed from.
+///
+/// Because the state in a [Store](crate::Store) is never mutated,
+/// only replaced, the output only needs to be re-computed when
+/// [select()](Selector::select()) is called with a different state
+/// (compared using [Rc::ptr_eq()]).
+///
+/// ## Example
+///
+/// ```
+/// use reactive_state::Selector;
+/// use std::rc::Rc;
+///
+/// struct State {
+///     items: Vec<u32>,
+/// }
+///
+/// let total = Selector::new(|state: &State| state.items.iter().sum::<u32>());
+///
+/// let state = Rc::new(State {
+///     items: vec![1, 2, 3],
+/// });
+///
+/// assert_eq!(6, total.select(&state));
+/// // The second time the output is taken from the cache.
+/// assert_eq!(6, total.select(&state));
+/// ```
+///
+/// See [Store::subscribe_selector()](crate::Store::subscribe_selector())
+/// for subscribing to changes in a selected value.
+pub struct Selector<State, Output> {
+    select: Box<dyn Fn(&State) -> Output>,
+    cache: RefCell<Option<SelectorCache<State, Output>>>,
+}
+
+impl<State, Output> Selector<State, Output>
+where
+    Output: Clone,
+{
+    /// Create a new [Selector] using the `select` function to compute
+    /// its output.
+    pub fn new<F: Fn(&State) -> Output + 'static>(select: F) -> Self {
+        Self {
+            select: Box::new(select),
+            cache: RefCell::new(None),
+        }
+    }
+
+    /// Compute the output of this selector for the specified `state`,
+    /// or return the cached output if the `state` is the same as the
+    /// one it was last computed for.
+    pub fn select(&self, state: &Rc<State>) -> Output {
+        if let Some(cache) = &*self.cache.borrow() {
+            if Weak::ptr_eq(&cache.state, &Rc::downgrade(state)) {
+                return cache.output.clone();
+            }
+        }
+
+        let output = (self.select)(state);
+        *self.cache.borrow_mut() = Some(SelectorCache {
+            state: Rc::downgrade(state),
+            output: output.clone(),
+        });
+        output
+    }
+}
+
+impl<State, Output> Debug for Selector<State, Output> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Selector")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Selector;
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn test_selector_memoized() {
+        let calls = Rc::new(Cell::new(0));
+        let calls_local = calls.clone();
+        let selector = Selector::new(move |state: &Vec<i32>| {
+            calls_local.set(calls_local.get() + 1);
+            state.len()
+        });
+
+        let state1 = Rc::new(vec![1, 2]);
+        assert_eq!(2, selector.select(&state1));
+        assert_eq!(2, selector.select(&state1.clone()));
+        assert_eq!(1, calls.get());
+
+        // An equal state in a different allocation is re-computed.
+        let state2 = Rc::new(vec![1, 2]);
+        assert_eq!(2, selector.select(&state2));
+        assert_eq!(2, calls.get());
+
+        drop(state1);
+        let state3 = Rc::new(vec![3]);
+        assert_eq!(1, selector.select(&state3));
+        assert_eq!(3, calls.get());
+    }
+}
@@ -1,37 +1,120 @@
 use crate::{
-    middleware::{Middleware, ReduceMiddlewareResult},
-    AsListener, Listener, Reducer,
+    dispatch::{DispatchCompleter, DispatchFuture, DispatchReport},
+    handle::RemoteQueue,
+    middleware::{
+        journal::{Journal, JournalCodec, JournalError, JournalLog},
+        Middleware, MiddlewareId, MiddlewarePosition, NotifyNext, ReduceMiddlewareResult,
+        ReduceNext,
+    },
+    panic::{PanicSource, StorePanic},
+    subscription::{
+        ErrorListenerRegistry, EventFilter, ListenerKind, ListenerRegistry, NotifyPolicy,
+        Unsubscribe,
+    },
+    transaction::{Transaction, TransactionCommit, TransactionError},
+    AsBatchListener, AsListener, Callback, ReduceError, Reducer, ScopedStore, Selector,
+    StoreHandle, Subscription, SubscriptionId,
 };
 use std::iter::FromIterator;
 use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
 use std::{
+    any::Any,
     cell::{Cell, RefCell},
     collections::{HashSet, VecDeque},
-    fmt::Debug,
     hash::Hash,
     marker::PhantomData,
-    rc::Rc,
+    mem::{self, Discriminant},
+    rc::{Rc, Weak},
+    sync::Arc,
 };
 
-/// A [Listener] associated with (listening to) a given set of
-/// `Events`s produced by a [Store::dispatch()].
-struct ListenerEventPair<State, Event> {
-    pub listener: Listener<State, Event>,
-    pub events: HashSet<Event>,
-}
-
-impl<State, Event> Debug for ListenerEventPair<State, Event> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ListenerEventPair")
-    }
-}
+/// Handler for panics caught by a [Store], see
+/// [Store::set_panic_handler()].
+type PanicHandler = Rc<dyn Fn(&StorePanic)>;
 
 /// An action to modify some aspect of the [Store], to be stored in a
 /// queue and executed at the start of a [Store::dispatch()] for a
 /// given `Action`.
 enum StoreModification<State, Action, Event, Effect> {
-    AddListener(ListenerEventPair<State, Event>),
-    AddMiddleware(Rc<dyn Middleware<State, Action, Event, Effect>>),
+    AddMiddleware {
+        entry: MiddlewareEntry<State, Action, Event, Effect>,
+        position: MiddlewarePosition,
+    },
+    RemoveMiddleware(MiddlewareId),
+}
+
+/// [Middleware] which has been added to a [Store], along with its
+/// [MiddlewareId].
+struct MiddlewareEntry<State, Action, Event, Effect> {
+    id: MiddlewareId,
+    middleware: Rc<dyn Middleware<State, Action, Event, Effect>>,
+}
+
+/// An item waiting in the [Store]'s dispatch queue.
+enum QueuedDispatch<State, Action, Event, Effect> {
+    /// An `Action` to be reduced, along with the [DispatchCompleter]
+    /// to resolve once it has been processed, if it was dispatched via
+    /// [Store::dispatch_async()].
+    Action {
+        action: Action,
+        completer: Option<DispatchCompleter<State, Event>>,
+        /// Whether this action was dispatched via
+        /// [Store::dispatch()], and its [DispatchReport] is required.
+        reported: bool,
+    },
+    /// A replacement for the store's state, see
+    /// [Store::replace_state()].
+    ReplaceState {
+        state: Rc<State>,
+        events: Vec<Event>,
+    },
+    /// Items which are processed together, notifying the listeners
+    /// once after all of them, see [Store::dispatch_batch()] and
+    /// [Store::batch()].
+    Batch(Vec<QueuedDispatch<State, Action, Event, Effect>>),
+    /// The result of a [Store::transaction()] to be committed.
+    Commit {
+        commit: TransactionCommit<State, Action, Event, Effect>,
+        /// Receives the error if the commit was rejected.
+        rejected: Rc<RefCell<Option<ReduceError>>>,
+    },
+}
+
+impl<State, Action, Event, Effect> QueuedDispatch<State, Action, Event, Effect> {
+    /// The number of actions which are processed with this item.
+    fn action_count(&self) -> usize {
+        match self {
+            QueuedDispatch::Action { .. } => 1,
+            QueuedDispatch::Batch(batch) => batch.iter().map(Self::action_count).sum(),
+            QueuedDispatch::ReplaceState { .. } | QueuedDispatch::Commit { .. } => 0,
+        }
+    }
+}
+
+/// Guard for a [Store::batch()] scope, which leaves the scope when it
+/// is dropped, even if the function called during the scope panics.
+struct BatchScope<'a, State, Action, Event, Effect> {
+    store: &'a Store<State, Action, Event, Effect>,
+}
+
+impl<'a, State, Action, Event, Effect> BatchScope<'a, State, Action, Event, Effect> {
+    fn enter(store: &'a Store<State, Action, Event, Effect>) -> Self {
+        store.batch_depth.set(store.batch_depth.get() + 1);
+        Self { store }
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Drop for BatchScope<'a, State, Action, Event, Effect> {
+    fn drop(&mut self) {
+        let depth = self.store.batch_depth.get() - 1;
+        self.store.batch_depth.set(depth);
+
+        // The batch of a scope which panicked is discarded.
+        if depth == 0 && std::thread::panicking() {
+            self.store.batch_queue.borrow_mut().clear();
+        }
+    }
 }
 
 /// A wrapper for an [Rc] reference to a [Store].
@@ -44,7 +127,7 @@ pub struct StoreRef<State, Action, Event, Effect>(Rc<Store<State, Action, Event,
 
 impl<State, Action, Event, Effect> StoreRef<State, Action, Event, Effect>
 where
-    Event: Clone + Hash + Eq,
+    Event: Clone,
 {
     pub fn new<R: Reducer<State, Action, Event, Effect> + 'static>(
         reducer: R,
@@ -52,6 +135,32 @@ where
     ) -> Self {
         Self(Rc::new(Store::new(reducer, initial_state)))
     }
+
+    /// Create a [ScopedStore], a view of the `Rc<SubState>` field of
+    /// this store's state selected by the `lens` function, which
+    /// dispatches actions to this store after mapping them with
+    /// `action_map`.
+    pub fn scope<SubState, SubAction, L, M>(
+        &self,
+        lens: L,
+        action_map: M,
+    ) -> ScopedStore<State, Action, Event, Effect, SubState, SubAction>
+    where
+        L: Fn(&State) -> &Rc<SubState> + 'static,
+        M: Fn(SubAction) -> Action + 'static,
+    {
+        ScopedStore::new(self.clone(), lens, action_map)
+    }
+}
+
+impl<State, Action, Event, Effect> StoreRef<State, Action, Event, Effect> {
+    /// Create a [WeakStoreRef] to the same [Store], which does not
+    /// keep it alive. This is useful for middleware which needs to
+    /// dispatch actions to the store it has been added to, without
+    /// creating a reference cycle.
+    pub fn downgrade(&self) -> WeakStoreRef<State, Action, Event, Effect> {
+        WeakStoreRef(Rc::downgrade(&self.0))
+    }
 }
 
 impl<State, Action, Event, Effect> Clone for StoreRef<State, Action, Event, Effect> {
@@ -64,7 +173,7 @@ impl<State, Action, Event, Effect> Deref for StoreRef<State, Action, Event, Effe
     type Target = Store<State, Action, Event, Effect>;
 
     fn deref(&self) -> &Self::Target {
-        &*self.0
+        &self.0
     }
 }
 
@@ -74,6 +183,24 @@ impl<State, Action, Event, Effect> PartialEq for StoreRef<State, Action, Event,
     }
 }
 
+/// A weak reference to a [Store], created using
+/// [StoreRef::downgrade()].
+pub struct WeakStoreRef<State, Action, Event, Effect>(Weak<Store<State, Action, Event, Effect>>);
+
+impl<State, Action, Event, Effect> WeakStoreRef<State, Action, Event, Effect> {
+    /// Attempt to upgrade this weak reference to a [StoreRef],
+    /// otherwise if the [Store] has been dropped, returns `None`.
+    pub fn upgrade(&self) -> Option<StoreRef<State, Action, Event, Effect>> {
+        self.0.upgrade().map(StoreRef)
+    }
+}
+
+impl<State, Action, Event, Effect> Clone for WeakStoreRef<State, Action, Event, Effect> {
+    fn clone(&self) -> Self {
+        Self(Weak::clone(&self.0))
+    }
+}
+
 /// This struct is designed to operate as a central source of truth
 /// and global "immutable" state within your application.
 ///
@@ -95,7 +222,15 @@ pub struct Store<State, Action, Event, Effect> {
     /// This lock is used to prevent dispatch recursion.
     dispatch_lock: RefCell<()>,
     /// Queue of actions to be dispatched by [Store::dispatch()].
-    dispatch_queue: RefCell<VecDeque<Action>>,
+    dispatch_queue: RefCell<VecDeque<QueuedDispatch<State, Action, Event, Effect>>>,
+    /// The number of [Store::batch()] scopes currently executing.
+    batch_depth: Cell<usize>,
+    /// Items dispatched during a [Store::batch()] scope, to be
+    /// dispatched as a single batch once the outermost scope ends.
+    batch_queue: RefCell<Vec<QueuedDispatch<State, Action, Event, Effect>>>,
+    /// Queue of actions posted from other threads via a
+    /// [StoreHandle], to be dispatched by [Store::drain_remote()].
+    remote_queue: Arc<RemoteQueue<Action>>,
     /// Queue of [StoreModification]s to be executed by
     /// [Store::dispatch()] before the next `Action` is dispatched.
     modification_queue: RefCell<VecDeque<StoreModification<State, Action, Event, Effect>>>,
@@ -103,48 +238,202 @@ pub struct Store<State, Action, Event, Effect> {
     /// the `State` stored in this store, and produces `Events` to be
     /// sent to the store listeners.
     reducer: Box<dyn Reducer<State, Action, Event, Effect>>,
+    /// Whether actions are reduced using [Reducer::try_reduce()], see
+    /// [Store::new_fallible()].
+    fallible: bool,
+    /// Whether the actions of a [Store::transaction()] are being
+    /// committed, which are always reduced using
+    /// [Reducer::try_reduce()].
+    committing: Cell<bool>,
+    /// The error produced by the [Reducer] for the action currently
+    /// being reduced, if it was rejected.
+    reduce_error: RefCell<Option<ReduceError>>,
     /// The current state of this store.
     state: RefCell<Rc<State>>,
     /// The listeners which are notified of changes to the state of
     /// this store, and events produced by this store during a
     /// [Store::dispatch()].
-    listeners: RefCell<Vec<ListenerEventPair<State, Event>>>,
+    listeners: Rc<ListenerRegistry<State, Event>>,
+    /// The listeners which are notified when the [Reducer] rejects an
+    /// action, see [Store::subscribe_errors()].
+    error_listeners: Rc<ErrorListenerRegistry>,
+    /// When listeners subscribed to all changes are notified, unless
+    /// they were subscribed with their own policy, see
+    /// [Store::set_notify_policy()].
+    notify_policy: Cell<NotifyPolicy>,
     /// Middleware which modifies the functionality of this store.
     #[allow(clippy::type_complexity)]
-    middleware: RefCell<Vec<Rc<dyn Middleware<State, Action, Event, Effect>>>>,
-    /// Used during recursive execution of [Middleware] to keep track
-    /// of the middleware currently executing. It is an index into
-    /// [Store::middleware].
-    prev_middleware: Cell<i32>,
+    middleware: RefCell<Vec<MiddlewareEntry<State, Action, Event, Effect>>>,
+    /// The id to give to the next [Middleware] added to this store.
+    next_middleware_id: Cell<u64>,
+    /// Invoked with panics caught during dispatch, see
+    /// [Store::set_panic_handler()].
+    panic_handler: RefCell<Option<PanicHandler>>,
     phantom_action: PhantomData<Action>,
     phantom_event: PhantomData<Event>,
 }
 
+/// The parts of the [Store] used by the middleware chain, which
+/// [ReduceNext] and [NotifyNext] can call without the `Event` bounds.
+impl<State, Action, Event, Effect> Store<State, Action, Event, Effect> {
+    /// The middleware at position `index` in the chain, if there is
+    /// one.
+    fn middleware_at(
+        &self,
+        index: usize,
+    ) -> Option<Rc<dyn Middleware<State, Action, Event, Effect>>> {
+        self.middleware
+            .borrow()
+            .get(index)
+            .map(|entry| entry.middleware.clone())
+    }
+
+    /// Dispatch an `Action` to the reducer on this `Store` without
+    /// invoking middleware.
+    fn dispatch_reducer(&self, action: &Action) -> ReduceMiddlewareResult<Event, Effect> {
+        let state = self.state.borrow().clone();
+        let result = if self.fallible || self.committing.get() {
+            match self.reducer.try_reduce(&state, action) {
+                Ok(result) => result,
+                Err(error) => {
+                    *self.reduce_error.borrow_mut() = Some(error);
+                    return ReduceMiddlewareResult::default();
+                }
+            }
+        } else {
+            self.reducer.reduce(&state, action)
+        };
+        *self.state.borrow_mut() = result.state;
+
+        ReduceMiddlewareResult {
+            events: result.events,
+            effects: result.effects,
+        }
+    }
+
+    /// Returns `true` if the [Reducer] rejected the action which is
+    /// currently being reduced. This can be used by middleware after
+    /// invoking [ReduceNext::reduce()].
+    pub(crate) fn reduce_rejected(&self) -> bool {
+        self.reduce_error.borrow().is_some()
+    }
+
+    /// A recursive function which executes the middleware at position
+    /// `index` in the chain, giving it a [ReduceNext] to invoke the
+    /// next middleware, until all middleware has been invoked, at
+    /// which point the `Action` is sent to the reducer.
+    pub(crate) fn middleware_reduce_next(
+        &self,
+        index: usize,
+        action: Option<&Action>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        match self.middleware_at(index) {
+            Some(middleware) => {
+                middleware.on_reduce(self, action, ReduceNext::new(self, index + 1))
+            }
+            None => match action {
+                Some(action) => self.dispatch_reducer(action),
+                None => ReduceMiddlewareResult::default(),
+            },
+        }
+    }
+
+    /// A recursive function which executes the middleware at position
+    /// `index` in the chain, giving it a [NotifyNext] to invoke the
+    /// next middleware, until all middleware has been invoked, at
+    /// which point the listeners are notified of the envents produced
+    /// during a reduce as a result of an `Action` being dispatched.
+    /// Notification occurs even if there are no events to report.
+    pub(crate) fn middleware_notify_next(&self, index: usize, events: Vec<Event>) -> Vec<Event> {
+        match self.middleware_at(index) {
+            Some(middleware) => {
+                middleware.on_notify(self, events, NotifyNext::new(self, index + 1))
+            }
+            None => events,
+        }
+    }
+}
+
 impl<State, Action, Event, Effect> Store<State, Action, Event, Effect>
 where
-    Event: Clone + Hash + Eq,
+    Event: Clone,
 {
     /// Create a new [Store], which uses the specified `reducer` to
     /// handle `Action`s which mutate the state and produce `Event`s,
     /// and with the `initial_state`.
+    ///
+    /// If the `reducer` is fallible (see [Reducer::is_fallible()]),
+    /// the store behaves as if it was created with
+    /// [new_fallible()](Store::new_fallible()).
     pub fn new<R: Reducer<State, Action, Event, Effect> + 'static>(
         reducer: R,
         initial_state: State,
     ) -> Self {
+        let fallible = reducer.is_fallible();
         Self {
             dispatch_lock: RefCell::new(()),
             dispatch_queue: RefCell::new(VecDeque::new()),
+            batch_depth: Cell::new(0),
+            batch_queue: RefCell::new(Vec::new()),
+            remote_queue: Arc::new(RemoteQueue::new()),
             modification_queue: RefCell::new(VecDeque::new()),
             reducer: Box::new(reducer),
+            fallible,
+            committing: Cell::new(false),
+            reduce_error: RefCell::new(None),
             state: RefCell::new(Rc::new(initial_state)),
-            listeners: RefCell::new(Vec::new()),
+            listeners: Rc::new(ListenerRegistry::new()),
+            error_listeners: Rc::new(ErrorListenerRegistry::new()),
+            notify_policy: Cell::new(NotifyPolicy::default()),
             middleware: RefCell::new(Vec::new()),
-            prev_middleware: Cell::new(-1),
+            next_middleware_id: Cell::new(0),
+            panic_handler: RefCell::new(None),
             phantom_action: PhantomData,
             phantom_event: PhantomData,
         }
     }
 
+    /// Create a new [Store], which uses the specified `reducer` to
+    /// handle `Action`s with [Reducer::try_reduce()], and with the
+    /// `initial_state`.
+    ///
+    /// When the `reducer` rejects an action, the state is left
+    /// untouched, no events are produced, and the error is sent to
+    /// each middleware's [Middleware::on_error()], then the error
+    /// listeners (see [subscribe_errors()](Store::subscribe_errors())),
+    /// and returned in the [DispatchReport].
+    pub fn new_fallible<R: Reducer<State, Action, Event, Effect> + 'static>(
+        reducer: R,
+        initial_state: State,
+    ) -> Self {
+        let mut store = Self::new(reducer, initial_state);
+        store.fallible = true;
+        store
+    }
+
+    /// Create a new [Store] with the state reconstructed from a
+    /// [Journal], by running the journaled actions through the
+    /// `reducer`, starting from the most recent snapshot in the
+    /// journal, or the `initial_state` if it has none. No effects are
+    /// processed, and no middleware is invoked.
+    ///
+    /// See [journal](crate::middleware::journal) for more details.
+    pub fn replay<R, C, L>(
+        reducer: R,
+        initial_state: State,
+        journal: &Journal<State, Action, C, L>,
+    ) -> Result<Self, JournalError>
+    where
+        R: Reducer<State, Action, Event, Effect> + 'static,
+        C: JournalCodec<State, Action>,
+        L: JournalLog,
+    {
+        let store = Self::new(reducer, initial_state);
+        let state = journal.replay(store.reducer(), store.state())?;
+        *store.state.borrow_mut() = state;
+        Ok(store)
+    }
+
     /// Get the current `State` stored in this store.
     ///
     /// Modifications to this state need to be performed by
@@ -154,155 +443,200 @@ where
         self.state.borrow().clone()
     }
 
-    /// Dispatch an `Action` to the reducer on this `Store` without
-    /// invoking middleware.
-    fn dispatch_reducer(&self, action: &Action) -> ReduceMiddlewareResult<Event, Effect> {
-        let result = self.reducer.reduce(&self.state(), action);
-        *self.state.borrow_mut() = result.state;
-
-        ReduceMiddlewareResult {
-            events: result.events,
-            effects: result.effects,
+    /// Dispatch an `Action` to the reducer on this `Store`, invoking
+    /// the middleware first if there is any. If the reducer rejected
+    /// the action, the middleware and error listeners are notified,
+    /// and the error is returned.
+    fn reduce_action(
+        &self,
+        action: &Action,
+    ) -> (ReduceMiddlewareResult<Event, Effect>, Option<ReduceError>) {
+        let result = if self.middleware.borrow().is_empty() {
+            self.dispatch_reducer(action)
+        } else {
+            self.middleware_reduce(action)
+        };
+
+        let error = self.reduce_error.borrow_mut().take();
+        if let Some(error) = &error {
+            self.notify_error(error);
         }
+        (result, error)
     }
 
-    /// Dispatch an `Action` to the reducer on this `Store`, invoking
-    /// all middleware's [reduce()][Middleware::reduce()] first.
-    fn middleware_reduce(&self, action: &Action) -> ReduceMiddlewareResult<Event, Effect> {
-        self.prev_middleware.set(-1);
-        self.middleware_reduce_next(Some(action))
+    /// Invoke all middleware's [Middleware::on_replace_state()] after
+    /// the store's state has been replaced with `state`.
+    fn middleware_replace_state(&self, state: &Rc<State>) {
+        let middleware: Vec<_> = self
+            .middleware
+            .borrow()
+            .iter()
+            .map(|entry| entry.middleware.clone())
+            .collect();
+        for middleware in middleware {
+            middleware.on_replace_state(self, state);
+        }
     }
 
-    /// A recursive function which executes each middleware for this
-    /// store, and invokes the next middleware, until all middleware
-    /// has been invoked, at which point the `Action` is sent to the
-    /// reducer.
-    fn middleware_reduce_next(
-        &self,
-        action: Option<&Action>,
-    ) -> ReduceMiddlewareResult<Event, Effect> {
-        let current_middleware = self.prev_middleware.get() + 1;
-        self.prev_middleware.set(current_middleware);
+    /// Notify the middleware and error listeners that the reducer
+    /// rejected an action.
+    fn notify_error(&self, error: &ReduceError) {
+        let middleware: Vec<_> = self
+            .middleware
+            .borrow()
+            .iter()
+            .map(|entry| entry.middleware.clone())
+            .collect();
+        for middleware in middleware {
+            self.guard(PanicSource::Middleware, || middleware.on_error(self, error));
+        }
 
-        if current_middleware == self.middleware.borrow().len() as i32 {
-            return match action {
-                Some(action) => self.dispatch_reducer(action),
-                None => ReduceMiddlewareResult::default(),
-            };
+        for callback in self.error_listeners.snapshot() {
+            self.guard(PanicSource::Listener, || callback(error));
         }
+    }
 
-        self.middleware.borrow()[current_middleware as usize]
-            .clone()
-            .on_reduce(self, action, Self::middleware_reduce_next)
+    /// Dispatch an `Action` to the reducer on this `Store`, invoking
+    /// all middleware's [Middleware::on_reduce()] first.
+    fn middleware_reduce(&self, action: &Action) -> ReduceMiddlewareResult<Event, Effect> {
+        self.middleware_reduce_next(0, Some(action))
     }
 
     /// Process all the `Effect`s returned by the [Reducer::reduce()]
     /// by invoking the middleware on this store to perform the
-    /// processing using [Middleware::process_effect()].q
-    fn middleware_process_effects(&self, effects: Vec<Effect>) {
-        for effect in effects {
-            self.middleware_process_effect(effect);
-        }
+    /// processing using [Middleware::process_effect()]. Returns the
+    /// effects which were not consumed by any middleware.
+    fn middleware_process_effects(&self, effects: Vec<Effect>) -> Vec<Effect> {
+        effects
+            .into_iter()
+            .filter_map(|effect| self.middleware_process_effect(effect))
+            .collect()
     }
 
     /// Process the specified `Effect`, invoking all middleware in this
     /// store to perform the processing using
-    /// [Middleware::process_effect()].
-    fn middleware_process_effect(&self, effect: Effect) {
-        self.prev_middleware.set(-1);
-        self.middleware_process_effects_next(effect);
+    /// [Middleware::process_effect()]. Returns the effect if it was
+    /// not consumed by any middleware.
+    fn middleware_process_effect(&self, effect: Effect) -> Option<Effect> {
+        self.middleware_process_effects_next(0, effect)
     }
 
-    /// A recursive function which executes each middleware for this
-    /// store to process the specified `Effect` with
+    /// A recursive function which executes the middleware at position
+    /// `index` in the chain to process the specified `Effect` with
     /// [Middleware::process_effect()], and invokes the next
     /// middleware, until all middleware has been invoked.
-    fn middleware_process_effects_next(&self, effect: Effect) {
-        let current_middleware = self.prev_middleware.get() + 1;
-        self.prev_middleware.set(current_middleware);
-
-        if current_middleware == self.middleware.borrow().len() as i32 {
-            return;
-        }
-
-        if let Some(effect) = self.middleware.borrow()[current_middleware as usize]
-            .clone()
-            .process_effect(self, effect)
-        {
-            self.middleware_process_effects_next(effect);
+    fn middleware_process_effects_next(&self, index: usize, effect: Effect) -> Option<Effect> {
+        match self.middleware_at(index) {
+            Some(middleware) => middleware
+                .process_effect(self, effect)
+                .and_then(|effect| self.middleware_process_effects_next(index + 1, effect)),
+            None => Some(effect),
         }
     }
 
     /// Notify store listeners of events produced during a reduce as a
     /// result of an `Action` being dispatched. Invokes all
-    /// middleware's [reduce()][Middleware::reduce()] first.
+    /// middleware's [Middleware::on_notify()] first.
     /// Notification occurs even if there are no events to report.
     fn middleware_notify(&self, events: Vec<Event>) -> Vec<Event> {
-        self.prev_middleware.set(-1);
-        self.middleware_notify_next(events)
-    }
-
-    /// A recursive function which executes each middleware for this
-    /// store, and invokes the next middleware, until all middleware
-    /// has been invoked, at which point the listeners are notified of
-    /// the envents produced during a reduce as a result of an
-    /// `Action` being dispatched. Notification occurs even if there
-    /// are no events to report.
-    fn middleware_notify_next(&self, events: Vec<Event>) -> Vec<Event> {
-        let current_middleware = self.prev_middleware.get() + 1;
-        self.prev_middleware.set(current_middleware);
-
-        if current_middleware == self.middleware.borrow().len() as i32 {
-            return events;
-        }
-
-        self.middleware.borrow()[current_middleware as usize]
-            .clone()
-            .on_notify(self, events, Self::middleware_notify_next)
+        self.middleware_notify_next(0, events)
     }
 
     /// Notify store listeners of events produced during a result of
     /// an `Action` being dispatched. Notification occurs even if
     /// there are no events to report.
-    fn notify_listeners(&self, events: Vec<Event>) {
-        let mut listeners_to_remove: Vec<usize> = Vec::new();
-        for (i, pair) in self.listeners.borrow().iter().enumerate() {
-            let retain = match pair.listener.as_callback() {
-                Some(callback) => {
-                    if pair.events.is_empty() {
-                        callback.emit(self.state.borrow().clone(), None);
-                    } else {
-                        //  call the listener for every matching listener event
-                        for event in &events {
-                            if pair.events.contains(event) {
-                                callback.emit(self.state.borrow().clone(), Some(event.clone()));
+    fn notify_listeners(&self, events: &[Event], state_changed: bool) {
+        let default_policy = self.notify_policy.get();
+        let mut remove_dead = false;
+        for entry in self.listeners.snapshot() {
+            // The listener may have been unsubscribed by a previous
+            // callback during this notification.
+            if !entry.active.get() {
+                continue;
+            }
+
+            // Whether a listener subscribed to all changes is notified.
+            let notify_all = entry
+                .policy
+                .unwrap_or(default_policy)
+                .should_notify(!events.is_empty(), state_changed);
+
+            match &entry.listener {
+                ListenerKind::Single(listener) => match listener.as_callback() {
+                    Some(callback) => {
+                        if let EventFilter::All = entry.filter {
+                            if notify_all {
+                                let state = self.state();
+                                self.guard(PanicSource::Listener, || callback.emit(state, None));
+                            }
+                        } else {
+                            //  call the listener for every matching listener event
+                            for event in events {
+                                if entry.filter.matches(event) {
+                                    let state = self.state();
+                                    self.guard(PanicSource::Listener, || {
+                                        callback.emit(state, Some(event.clone()))
+                                    });
+                                }
                             }
                         }
                     }
-
-                    true
-                }
-                None => false,
-            };
-
-            if !retain {
-                listeners_to_remove.insert(0, i);
+                    None => remove_dead = true,
+                },
+                ListenerKind::Batch(listener) => match listener.as_callback() {
+                    Some(callback) => {
+                        // call the listener once with all the matching events
+                        if let EventFilter::All = entry.filter {
+                            if notify_all {
+                                let state = self.state();
+                                self.guard(PanicSource::Listener, || callback.emit(state, events));
+                            }
+                        } else {
+                            let matching: Vec<Event> = events
+                                .iter()
+                                .filter(|event| entry.filter.matches(event))
+                                .cloned()
+                                .collect();
+                            if !matching.is_empty() {
+                                let state = self.state();
+                                self.guard(PanicSource::Listener, || {
+                                    callback.emit(state, &matching)
+                                });
+                            }
+                        }
+                    }
+                    None => remove_dead = true,
+                },
             }
         }
 
-        for index in listeners_to_remove {
-            self.listeners.borrow_mut().swap_remove(index);
+        if remove_dead {
+            self.listeners.remove_dead();
         }
     }
 
     fn process_pending_modifications(&self) {
         while let Some(modification) = self.modification_queue.borrow_mut().pop_front() {
             match modification {
-                StoreModification::AddListener(listener_pair) => {
-                    self.listeners.borrow_mut().push(listener_pair);
+                StoreModification::AddMiddleware { entry, position } => {
+                    let mut middleware = self.middleware.borrow_mut();
+                    let index_of =
+                        |id: MiddlewareId| middleware.iter().position(|entry| entry.id == id);
+
+                    let index = match position {
+                        MiddlewarePosition::First => Some(0),
+                        MiddlewarePosition::Last => None,
+                        MiddlewarePosition::Before(id) => index_of(id),
+                        MiddlewarePosition::After(id) => index_of(id).map(|index| index + 1),
+                    };
+
+                    match index {
+                        Some(index) => middleware.insert(index, entry),
+                        None => middleware.push(entry),
+                    }
                 }
-                StoreModification::AddMiddleware(middleware) => {
-                    self.middleware.borrow_mut().push(middleware);
+                StoreModification::RemoveMiddleware(id) => {
+                    self.middleware.borrow_mut().retain(|entry| entry.id != id);
                 }
             }
         }
@@ -311,8 +645,193 @@ where
     /// Dispatch an `Action` to be passed to the [Reducer] in order to
     /// modify the `State` in this store, and produce `Events` to be
     /// sent to the store listeners.
-    pub fn dispatch<A: Into<Action>>(&self, action: A) {
-        self.dispatch_impl(action.into());
+    ///
+    /// Returns a [DispatchReport] describing what the dispatch did,
+    /// which is mostly useful for assertions in tests.
+    pub fn dispatch<A: Into<Action>>(&self, action: A) -> DispatchReport<Event, Effect> {
+        self.dispatch_impl(action.into())
+    }
+
+    /// Dispatch multiple `Action`s, which are reduced (and have their
+    /// effects processed) in order, but with the listeners notified
+    /// only once after the last action, with the final state. The
+    /// events produced by all the actions are combined (in order,
+    /// including any repeated events) for [Middleware::on_notify()]
+    /// and the listeners.
+    ///
+    /// This avoids notifying the listeners (for example, refreshing a
+    /// user interface) for every action when many actions are
+    /// dispatched at once.
+    pub fn dispatch_batch<A: Into<Action>, I: IntoIterator<Item = A>>(&self, actions: I) {
+        let batch = actions
+            .into_iter()
+            .map(|action| QueuedDispatch::Action {
+                action: action.into(),
+                completer: None,
+                reported: false,
+            })
+            .collect();
+        self.dispatch_queued(QueuedDispatch::Batch(batch));
+    }
+
+    /// Execute `f`, collecting all the actions dispatched to this
+    /// store (and states replaced with
+    /// [replace_state()](Store::replace_state())) during it, and
+    /// dispatching them as a single batch in the same manner as
+    /// [dispatch_batch()](Store::dispatch_batch()) once it returns.
+    /// Nested scopes are dispatched as part of the outermost scope's
+    /// batch.
+    ///
+    /// Futures returned by [dispatch_async()](Store::dispatch_async())
+    /// during the scope resolve once the whole batch has been
+    /// processed, with the combined events.
+    ///
+    /// If `f` panics, the batch is discarded (the futures resolve as
+    /// [DispatchOutcome::Cancelled](crate::DispatchOutcome::Cancelled)),
+    /// and the store can continue to be used if the panic is caught.
+    pub fn batch<R, F: FnOnce(&Self) -> R>(&self, f: F) -> R {
+        let scope = BatchScope::enter(self);
+        let result = f(self);
+        drop(scope);
+
+        if self.batch_depth.get() == 0 {
+            let batch = std::mem::take(&mut *self.batch_queue.borrow_mut());
+            if !batch.is_empty() {
+                self.dispatch_queued(QueuedDispatch::Batch(batch));
+            }
+        }
+
+        result
+    }
+
+    /// Reduce a group of `Action`s as a single atomic transaction.
+    ///
+    /// The actions dispatched to the [Transaction] within `f` are
+    /// reduced against a working state (starting from the current
+    /// state), using [Reducer::try_reduce()], without invoking any
+    /// middleware. If `f` returns `Ok`, the transaction is committed:
+    /// the store's state is replaced with the working state, the
+    /// effects produced by the actions are processed, and the
+    /// listeners are notified once of the combined events (after
+    /// [Middleware::on_notify()]).
+    ///
+    /// If the store has any middleware, the actions are instead
+    /// committed by sending each of them through
+    /// [Middleware::on_reduce()] to the [Reducer] (still using
+    /// [Reducer::try_reduce()]), so that middleware which records
+    /// the dispatched actions (such as
+    /// [JournalMiddleware](crate::middleware::journal::JournalMiddleware))
+    /// sees them.
+    ///
+    /// Like actions, the commit is queued if a dispatch is already in
+    /// progress (or during [batch()](Store::batch())). If the store's
+    /// state has been replaced by the time the commit takes place,
+    /// the actions are reduced again against the new state, so that
+    /// the changes ahead of the commit are not lost.
+    ///
+    /// If `f` returns an error, or the reducer rejects one of the
+    /// actions, the transaction is rolled back, and the store is left
+    /// unchanged. If the reducer rejects one of the actions when they
+    /// are reduced again at commit time, the commit is rolled back,
+    /// and the error is sent to the store's error listeners (see
+    /// [subscribe_errors()](Store::subscribe_errors())). It is also
+    /// returned if the commit was not queued.
+    pub fn transaction<T, E, F>(&self, f: F) -> Result<T, TransactionError<E>>
+    where
+        F: FnOnce(&mut Transaction<'_, State, Action, Event, Effect>) -> Result<T, E>,
+    {
+        let mut transaction = Transaction::new(self.reducer(), self.state());
+        let result = f(&mut transaction);
+        let commit = transaction.finish().map_err(TransactionError::Rejected)?;
+        let value = result.map_err(TransactionError::Aborted)?;
+
+        let rejected = Rc::new(RefCell::new(None));
+        self.dispatch_queued(QueuedDispatch::Commit {
+            commit,
+            rejected: rejected.clone(),
+        });
+
+        let error = rejected.borrow_mut().take();
+        match error {
+            Some(error) => Err(TransactionError::Rejected(error)),
+            None => Ok(value),
+        }
+    }
+
+    /// Replace the store's state with the result of a transaction.
+    /// If the state has been replaced since the transaction started,
+    /// its actions are reduced again against the current state.
+    /// Returns the events and effects to process, or the error if the
+    /// reducer rejected an action, leaving the state unchanged.
+    ///
+    /// All the actions are reduced before any of them are sent through
+    /// the middleware, so a rejected commit leaves no partial record
+    /// in middleware such as the journal or time travel.
+    fn reduce_commit(
+        &self,
+        commit: TransactionCommit<State, Action, Event, Effect>,
+    ) -> Result<(Vec<Event>, Vec<Effect>), ReduceError> {
+        let current = self.state();
+        let (state, events, effects) = if Rc::ptr_eq(&commit.base, &current) {
+            (commit.state, commit.events, commit.effects)
+        } else {
+            let mut state = current.clone();
+            let mut events = Vec::new();
+            let mut effects = Vec::new();
+            for action in &commit.actions {
+                let result = self.reducer.try_reduce(&state, action)?;
+                state = result.state;
+                events.extend(result.events);
+                effects.extend(result.effects);
+            }
+            (state, events, effects)
+        };
+
+        if self.middleware.borrow().is_empty() {
+            *self.state.borrow_mut() = state;
+            return Ok((events, effects));
+        }
+
+        // The actions are known to succeed, unless the middleware
+        // replaces them with actions which the reducer rejects, in
+        // which case the state is rolled back.
+        let mut events = Vec::new();
+        let mut effects = Vec::new();
+        self.committing.set(true);
+        for action in &commit.actions {
+            let result = self.middleware_reduce(action);
+            let error = self.reduce_error.borrow_mut().take();
+            if let Some(error) = error {
+                self.committing.set(false);
+                *self.state.borrow_mut() = current;
+                return Err(error);
+            }
+            events.extend(result.events);
+            effects.extend(result.effects);
+        }
+        self.committing.set(false);
+        Ok((events, effects))
+    }
+
+    /// Dispatch an `Action` in the same manner as
+    /// [dispatch()](Store::dispatch()), returning a future which
+    /// resolves once this specific action has been reduced, its
+    /// effects have been processed, and the store listeners have been
+    /// notified.
+    ///
+    /// When this is called from within a listener callback or
+    /// middleware (while another dispatch is in progress), the action
+    /// is queued and only processed once the current action has
+    /// completed, so awaiting the future allows `async` code to
+    /// correctly sequence work which depends on the action.
+    pub fn dispatch_async<A: Into<Action>>(&self, action: A) -> DispatchFuture<State, Event> {
+        let (future, completer) = DispatchFuture::new();
+        self.dispatch_queued(QueuedDispatch::Action {
+            action: action.into(),
+            completer: Some(completer),
+            reported: false,
+        });
+        future
     }
 
     /// Concrete version of [Store::dispatch()], for code size
@@ -320,8 +839,42 @@ where
     /// this complex function per action that implements
     /// `Into<Action>`, it is expected that there will be many in a
     /// typical application.
-    fn dispatch_impl(&self, action: Action) {
-        self.dispatch_queue.borrow_mut().push_back(action);
+    fn dispatch_impl(&self, action: Action) -> DispatchReport<Event, Effect> {
+        let prev_state = self.state();
+        let (actions_processed, report) = self.dispatch_queued(QueuedDispatch::Action {
+            action,
+            completer: None,
+            reported: true,
+        });
+
+        match report {
+            Some(mut report) => {
+                report.state_changed = !Rc::ptr_eq(&prev_state, &self.state());
+                report.actions_processed = actions_processed;
+                report
+            }
+            None => DispatchReport::queued(),
+        }
+    }
+
+    /// Add an action to the dispatch queue, and process the queue if
+    /// a dispatch is not already in progress. Returns the number of
+    /// actions which were processed, and the [DispatchReport] for the
+    /// action which was queued with `reported` set, if it was
+    /// processed.
+    fn dispatch_queued(
+        &self,
+        queued: QueuedDispatch<State, Action, Event, Effect>,
+    ) -> (usize, Option<DispatchReport<Event, Effect>>) {
+        let mut actions_processed = 0;
+        let mut report = None;
+
+        if self.batch_depth.get() > 0 {
+            self.batch_queue.borrow_mut().push(queued);
+            return (actions_processed, report);
+        }
+
+        self.dispatch_queue.borrow_mut().push_back(queued);
 
         // If the lock fails to acquire, then the dispatch is already in progress.
         // This prevents recursion, when a listener callback also triggers another
@@ -334,25 +887,35 @@ where
                 let dispatch_action = self.dispatch_queue.borrow_mut().pop_front();
 
                 match dispatch_action {
-                    Some(action) => {
+                    Some(queued) => {
                         self.process_pending_modifications();
-
-                        let reduce_middleware_result = if self.middleware.borrow().is_empty() {
-                            self.dispatch_reducer(&action)
+                        actions_processed += queued.action_count();
+
+                        let queued_report = if self.panic_handler.borrow().is_some() {
+                            let prev_state = self.state();
+                            match panic::catch_unwind(AssertUnwindSafe(|| {
+                                self.process_queued(queued)
+                            })) {
+                                Ok(queued_report) => queued_report,
+                                Err(payload) => {
+                                    // Restore the store to how it was before
+                                    // this item was processed, and carry on
+                                    // with the rest of the queue.
+                                    *self.state.borrow_mut() = prev_state;
+                                    self.reduce_error.borrow_mut().take();
+                                    self.committing.set(false);
+                                    self.report_panic(PanicSource::Dispatch, payload);
+                                    None
+                                }
+                            }
                         } else {
-                            self.middleware_reduce(&action)
+                            self.process_queued(queued)
                         };
 
-                        #[allow(clippy::match_single_binding)] // destructuring the result
-                        match reduce_middleware_result {
-                            ReduceMiddlewareResult { events, effects } => {
-                                self.middleware_process_effects(effects);
-
-                                let middleware_events = self.middleware_notify(events);
-                                if !middleware_events.is_empty() {
-                                    self.notify_listeners(middleware_events);
-                                }
-                            }
+                        // Actions dispatched while this one is being
+                        // processed are queued after it.
+                        if report.is_none() {
+                            report = queued_report;
                         }
                     }
                     None => {
@@ -361,100 +924,781 @@ where
                 }
             }
         }
+
+        (actions_processed, report)
     }
 
-    /// Subscribe a [Listener] to changes in the store state and
-    /// events produced by the [Reducer] as a result of `Action`s
-    /// dispatched via [dispatch()](Store::dispatch()).
-    ///
-    /// The listener is a weak reference; when the strong reference
-    /// associated with it (usually [Callback](crate::Callback)) is
-    /// dropped, the listener will be removed from this store upon
-    /// [dispatch()](Store::dispatch()).
-    ///
-    /// If you want to subscribe to state changes associated with
-    /// specific `Event`s, see
-    /// [subscribe_event()](Store::subscribe_events()) or
-    /// [subscribe_event()](Store::subscribe_events())
-    pub fn subscribe<L: AsListener<State, Event>>(&self, listener: L) {
-        self.modification_queue
-            .borrow_mut()
-            .push_back(StoreModification::AddListener(ListenerEventPair {
-                listener: listener.as_listener(),
-                events: HashSet::new(),
-            }));
+    /// Process an item taken from the dispatch queue. Returns the
+    /// [DispatchReport] if it was an action dispatched via
+    /// [Store::dispatch()].
+    fn process_queued(
+        &self,
+        queued: QueuedDispatch<State, Action, Event, Effect>,
+    ) -> Option<DispatchReport<Event, Effect>> {
+        match queued {
+            QueuedDispatch::Action {
+                action,
+                completer,
+                reported,
+            } => {
+                let report = self.process_action(action, completer);
+                if reported {
+                    return Some(report);
+                }
+            }
+            QueuedDispatch::ReplaceState { state, events } => {
+                let prev_state = self.state.replace(state);
+                self.middleware_replace_state(&self.state());
+                let state_changed = !Rc::ptr_eq(&prev_state, &self.state());
+                self.notify_listeners(&events, state_changed);
+            }
+            QueuedDispatch::Batch(batch) => {
+                self.process_batch(batch);
+            }
+            commit @ QueuedDispatch::Commit { .. } => {
+                self.process_batch(vec![commit]);
+            }
+        }
+
+        None
     }
 
-    /// Subscribe a [Listener] to changes in the store state and
-    /// events produced by the [Reducer] as a result of `Action`s
-    /// being dispatched via [dispatch()](Store::dispatch()) and
-    /// reduced with the store's [Reducer]. This subscription is only
-    /// active changes which produce the specific matching `event`
-    /// from the [Reducer].
-    ///
-    /// The listener is a weak reference; when the strong reference
-    /// associated with it (usually [Callback](crate::Callback)) is
-    /// dropped, the listener will be removed from this store upon
-    /// [dispatch()](Store::dispatch()).
-    pub fn subscribe_event<L: AsListener<State, Event>>(&self, listener: L, event: Event) {
-        let mut events = HashSet::with_capacity(1);
-        events.insert(event);
+    /// Invoke `f`, which calls into user code. If a panic handler has
+    /// been set with [Store::set_panic_handler()], a panic in `f` is
+    /// caught and reported to it as coming from `source`.
+    fn guard<F: FnOnce()>(&self, source: PanicSource, f: F) {
+        if self.panic_handler.borrow().is_none() {
+            f();
+            return;
+        }
 
-        self.modification_queue
-            .borrow_mut()
-            .push_back(StoreModification::AddListener(ListenerEventPair {
-                listener: listener.as_listener(),
-                events,
-            }));
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+            self.report_panic(source, payload);
+        }
     }
 
-    /// Subscribe a [Listener] to changes in the store state and
-    /// events produced by the [Reducer] as a result of `Action`s
-    /// being dispatched via [dispatch()](Store::dispatch()) and
-    /// reduced with the store's [Reducer]. This subscription is only
-    /// active changes which produce any of the specific matching
-    /// `events` from the [Reducer].
-    ///
-    /// The listener is a weak reference; when the strong reference
-    /// associated with it (usually [Callback](crate::Callback)) is
-    /// dropped, the listener will be removed from this store upon
-    /// [dispatch()](Store::dispatch()).
-    pub fn subscribe_events<L: AsListener<State, Event>, E: IntoIterator<Item = Event>>(
-        &self,
-        listener: L,
-        events: E,
-    ) {
-        self.modification_queue
-            .borrow_mut()
-            .push_back(StoreModification::AddListener(ListenerEventPair {
-                listener: listener.as_listener(),
-                events: HashSet::from_iter(events.into_iter()),
-            }));
+    /// Send a caught panic to the handler set with
+    /// [Store::set_panic_handler()].
+    fn report_panic(&self, source: PanicSource, payload: Box<dyn Any + Send>) {
+        let handler = self.panic_handler.borrow().clone();
+        if let Some(handler) = handler {
+            handler(&StorePanic::new(source, payload));
+        }
     }
 
-    /// Add [Middleware] to modify the behaviour of this [Store]
-    /// during a [dispatch()](Store::dispatch()).
-    pub fn add_middleware<M: Middleware<State, Action, Event, Effect> + 'static>(
+    /// Reduce an `Action` taken from the dispatch queue, process the
+    /// resulting effects, and notify the listeners.
+    fn process_action(
         &self,
-        middleware: M,
-    ) {
-        self.modification_queue
-            .borrow_mut()
-            .push_back(StoreModification::AddMiddleware(Rc::new(middleware)));
-    }
-}
+        action: Action,
+        completer: Option<DispatchCompleter<State, Event>>,
+    ) -> DispatchReport<Event, Effect> {
+        let prev_state = self.state();
+        let (reduce_middleware_result, mut error) = self.reduce_action(&action);
+
+        #[allow(clippy::match_single_binding)] // destructuring the result
+        match reduce_middleware_result {
+            ReduceMiddlewareResult { events, effects } => {
+                let effects_emitted = effects.len();
+                let unconsumed_effects = self.middleware_process_effects(effects);
+
+                let middleware_events = self.middleware_notify(events);
+                let state_changed = !Rc::ptr_eq(&prev_state, &self.state());
+                self.notify_listeners(&middleware_events, state_changed);
+
+                if let Some(completer) = completer {
+                    match error.take() {
+                        Some(error) => completer.reject(error),
+                        None => completer.complete(self.state(), middleware_events.clone()),
+                    }
+                }
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        middleware::{Middleware, ReduceMiddlewareResult},
-        Callback, Reducer, ReducerResult, Store, StoreRef,
-    };
-    use std::{cell::RefCell, rc::Rc};
+                DispatchReport {
+                    queued: false,
+                    events: middleware_events,
+                    effects_emitted,
+                    unconsumed_effects,
+                    state_changed: false,
+                    actions_processed: 0,
+                    error,
+                }
+            }
+        }
+    }
 
-    #[derive(Debug, PartialEq)]
-    struct TestState {
-        counter: i32,
+    /// Reduce the items in a batch taken from the dispatch queue and
+    /// process the resulting effects, then notify the listeners once
+    /// with the combined events.
+    fn process_batch(&self, batch: Vec<QueuedDispatch<State, Action, Event, Effect>>) {
+        let prev_state = self.state();
+        let mut events = Vec::new();
+        let mut completers = Vec::new();
+        self.reduce_batch(batch, &mut events, &mut completers);
+
+        let middleware_events = self.middleware_notify(events);
+        let state_changed = !Rc::ptr_eq(&prev_state, &self.state());
+        self.notify_listeners(&middleware_events, state_changed);
+
+        for (completer, error) in completers {
+            match error {
+                Some(error) => completer.reject(error),
+                None => completer.complete(self.state(), middleware_events.clone()),
+            }
+        }
+    }
+
+    /// Reduce the items in a batch, and process the resulting effects,
+    /// collecting the events produced and the [DispatchCompleter]s to
+    /// resolve once the listeners have been notified (along with the
+    /// error if the reducer rejected their action).
+    fn reduce_batch(
+        &self,
+        batch: Vec<QueuedDispatch<State, Action, Event, Effect>>,
+        events: &mut Vec<Event>,
+        completers: &mut Vec<(DispatchCompleter<State, Event>, Option<ReduceError>)>,
+    ) {
+        for queued in batch {
+            self.process_pending_modifications();
+
+            match queued {
+                QueuedDispatch::Action {
+                    action, completer, ..
+                } => {
+                    // The middleware and error listeners are notified
+                    // if the action is rejected.
+                    let (result, error) = self.reduce_action(&action);
+                    self.middleware_process_effects(result.effects);
+                    events.extend(result.events);
+                    if let Some(completer) = completer {
+                        completers.push((completer, error));
+                    }
+                }
+                QueuedDispatch::ReplaceState {
+                    state,
+                    events: replace_events,
+                } => {
+                    *self.state.borrow_mut() = state;
+                    self.middleware_replace_state(&self.state());
+                    events.extend(replace_events);
+                }
+                QueuedDispatch::Batch(batch) => self.reduce_batch(batch, events, completers),
+                QueuedDispatch::Commit { commit, rejected } => match self.reduce_commit(commit) {
+                    Ok((commit_events, effects)) => {
+                        self.middleware_process_effects(effects);
+                        events.extend(commit_events);
+                    }
+                    Err(error) => {
+                        self.notify_error(&error);
+                        *rejected.borrow_mut() = Some(error);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Replace the current `State` of this store with `state`, and
+    /// notify the store listeners of the `events` associated with the
+    /// change, without invoking the [Reducer] or any [Middleware]
+    /// other than [Middleware::on_replace_state()].
+    ///
+    /// This is intended for tooling which needs to restore a
+    /// previous state, such as
+    /// [TimeTravelMiddleware](crate::middleware::time_travel::TimeTravelMiddleware).
+    /// Like actions, the replacement is queued if a dispatch is
+    /// already in progress, and takes place once the actions ahead of
+    /// it in the queue have been processed.
+    pub fn replace_state(&self, state: Rc<State>, events: Vec<Event>) {
+        self.dispatch_queued(QueuedDispatch::ReplaceState { state, events });
+    }
+
+    /// The [Reducer] used by this store. This can be used to
+    /// re-compute states without dispatching actions to the store.
+    pub fn reducer(&self) -> &dyn Reducer<State, Action, Event, Effect> {
+        self.reducer.as_ref()
+    }
+
+    /// Create a [StoreHandle], which can be sent to other threads
+    /// and used to post `Action`s to this store. Posted actions are
+    /// dispatched on the thread which owns this store when
+    /// [drain_remote()](Store::drain_remote()) is called.
+    pub fn handle(&self) -> StoreHandle<Action> {
+        StoreHandle::new(&self.remote_queue)
+    }
+
+    /// Set a callback which is invoked (on the posting thread)
+    /// whenever an `Action` is posted to this store via a
+    /// [StoreHandle]. This can be used to wake up the thread which
+    /// owns this store, so that it calls
+    /// [drain_remote()](Store::drain_remote()).
+    pub fn set_remote_wake<W: Fn() + Send + Sync + 'static>(&self, wake: W) {
+        self.remote_queue.set_wake(Some(Arc::new(wake)));
+    }
+
+    /// Catch panics in the [Reducer], [Middleware] and listener
+    /// callbacks during dispatch, and send them to `handler` instead
+    /// of unwinding out of [Store::dispatch()].
+    ///
+    /// When the reducer or a middleware panics, the state is restored
+    /// to what it was before the action (or batch) being processed,
+    /// and the store carries on with the rest of the dispatch queue.
+    /// When a listener panics, the remaining listeners are still
+    /// notified, and likewise when a middleware panics in
+    /// [Middleware::on_error()].
+    pub fn set_panic_handler<F: Fn(&StorePanic) + 'static>(&self, handler: F) {
+        *self.panic_handler.borrow_mut() = Some(Rc::new(handler));
+    }
+
+    /// Stop catching panics, see
+    /// [set_panic_handler()](Store::set_panic_handler()).
+    pub fn clear_panic_handler(&self) {
+        *self.panic_handler.borrow_mut() = None;
+    }
+
+    /// Dispatch all the `Action`s which have been posted to this
+    /// store from other threads via a [StoreHandle], in the order
+    /// that they were posted. Returns the number of actions that
+    /// were dispatched.
+    pub fn drain_remote(&self) -> usize {
+        let actions = self.remote_queue.take();
+        let n_actions = actions.len();
+
+        for action in actions {
+            self.dispatch_impl(action);
+        }
+
+        n_actions
+    }
+
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// dispatched via [dispatch()](Store::dispatch()).
+    ///
+    /// The listener remains subscribed until the returned
+    /// [Subscription] is dropped. The listener is also a weak
+    /// reference; when the strong reference associated with it
+    /// (usually [Callback](crate::Callback)) is dropped, the listener
+    /// will be removed from this store upon
+    /// [dispatch()](Store::dispatch()).
+    ///
+    /// The listener is notified according to the store's
+    /// [NotifyPolicy] (see
+    /// [set_notify_policy()](Store::set_notify_policy())), by default
+    /// only when the dispatch produced events.
+    ///
+    /// If you want to subscribe to state changes associated with
+    /// specific `Event`s, see
+    /// [subscribe_event()](Store::subscribe_event()) or
+    /// [subscribe_events()](Store::subscribe_events())
+    pub fn subscribe<L: AsListener<State, Event>>(&self, listener: L) -> Subscription
+    where
+        State: 'static,
+        Event: 'static,
+    {
+        self.add_listener(
+            ListenerKind::Single(listener.as_listener()),
+            EventFilter::All,
+            None,
+            0,
+        )
+    }
+
+    /// Subscribe a [Listener] to changes in the store state, in the
+    /// same way as [subscribe()](Store::subscribe()), but notified
+    /// according to the specified `policy` instead of the store's
+    /// [NotifyPolicy].
+    pub fn subscribe_with_policy<L: AsListener<State, Event>>(
+        &self,
+        listener: L,
+        policy: NotifyPolicy,
+    ) -> Subscription
+    where
+        State: 'static,
+        Event: 'static,
+    {
+        self.add_listener(
+            ListenerKind::Single(listener.as_listener()),
+            EventFilter::All,
+            Some(policy),
+            0,
+        )
+    }
+
+    /// Subscribe a [Listener] to changes in the store state, in the
+    /// same way as [subscribe()](Store::subscribe()), but with the
+    /// specified `priority`.
+    ///
+    /// Listeners are notified in order of descending priority, and
+    /// listeners with the same priority are notified in the order
+    /// that they were subscribed. Listeners are subscribed with a
+    /// priority of `0` by default, so a listener which maintains a
+    /// cache derived from the state (which other listeners read) can
+    /// be subscribed with a positive priority to ensure it is
+    /// updated first. See also
+    /// [set_listener_priority()](Store::set_listener_priority()).
+    pub fn subscribe_with_priority<L: AsListener<State, Event>>(
+        &self,
+        listener: L,
+        priority: i32,
+    ) -> Subscription
+    where
+        State: 'static,
+        Event: 'static,
+    {
+        self.add_listener(
+            ListenerKind::Single(listener.as_listener()),
+            EventFilter::All,
+            None,
+            priority,
+        )
+    }
+
+    /// Change the priority of the listener with the specified `id`
+    /// (see [subscribe_with_priority()](Store::subscribe_with_priority())),
+    /// which can be used for any kind of subscription. The listener
+    /// is notified after the other listeners with the same priority,
+    /// as if it had just been subscribed. Returns `true` if the
+    /// listener is subscribed to this store.
+    ///
+    /// This does not affect a notification which is already in
+    /// progress.
+    pub fn set_listener_priority(&self, id: SubscriptionId, priority: i32) -> bool {
+        self.listeners.set_priority(id, priority)
+    }
+
+    /// Set when the listeners subscribed to all changes with
+    /// [subscribe()](Store::subscribe()) (or
+    /// [subscribe_selector()](Store::subscribe_selector())) are
+    /// notified. Listeners subscribed with
+    /// [subscribe_with_policy()](Store::subscribe_with_policy()) keep
+    /// their own policy, and listeners subscribed to specific events
+    /// are only ever notified of those events. The default is
+    /// [NotifyPolicy::OnEvents].
+    pub fn set_notify_policy(&self, policy: NotifyPolicy) {
+        self.notify_policy.set(policy);
+    }
+
+    /// When the listeners subscribed to all changes are notified, see
+    /// [set_notify_policy()](Store::set_notify_policy()).
+    pub fn notify_policy(&self) -> NotifyPolicy {
+        self.notify_policy.get()
+    }
+
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// being dispatched via [dispatch()](Store::dispatch()) and
+    /// reduced with the store's [Reducer]. This subscription is only
+    /// active changes which produce the specific matching `event`
+    /// from the [Reducer].
+    ///
+    /// The listener remains subscribed until the returned
+    /// [Subscription] is dropped, or the strong reference associated
+    /// with it (usually [Callback](crate::Callback)) is dropped.
+    pub fn subscribe_event<L: AsListener<State, Event>>(
+        &self,
+        listener: L,
+        event: Event,
+    ) -> Subscription
+    where
+        State: 'static,
+        Event: Hash + Eq + 'static,
+    {
+        let mut events = HashSet::with_capacity(1);
+        events.insert(event);
+
+        self.add_listener(
+            ListenerKind::Single(listener.as_listener()),
+            EventFilter::events(events),
+            None,
+            0,
+        )
+    }
+
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// being dispatched via [dispatch()](Store::dispatch()) and
+    /// reduced with the store's [Reducer]. This subscription is only
+    /// active changes which produce any of the specific matching
+    /// `events` from the [Reducer].
+    ///
+    /// The listener remains subscribed until the returned
+    /// [Subscription] is dropped, or the strong reference associated
+    /// with it (usually [Callback](crate::Callback)) is dropped.
+    pub fn subscribe_events<L: AsListener<State, Event>, E: IntoIterator<Item = Event>>(
+        &self,
+        listener: L,
+        events: E,
+    ) -> Subscription
+    where
+        State: 'static,
+        Event: Hash + Eq + 'static,
+    {
+        self.add_listener(
+            ListenerKind::Single(listener.as_listener()),
+            EventFilter::events(HashSet::from_iter(events)),
+            None,
+            0,
+        )
+    }
+
+    /// Subscribe a [BatchListener](crate::BatchListener) (usually
+    /// [BatchCallback](crate::BatchCallback)) to changes in the store
+    /// state and events produced by the [Reducer] as a result of
+    /// `Action`s being dispatched via [dispatch()](Store::dispatch()).
+    /// The listener is invoked at most once per dispatch, with all
+    /// the produced events which match any of the `events`. If
+    /// `events` is empty, the listener is subscribed to all changes
+    /// (according to the store's [NotifyPolicy]), and is invoked with
+    /// all the produced events.
+    ///
+    /// The listener remains subscribed until the returned
+    /// [Subscription] is dropped, or the strong reference associated
+    /// with it (usually [BatchCallback](crate::BatchCallback)) is
+    /// dropped.
+    pub fn subscribe_batch<L, E>(&self, listener: L, events: E) -> Subscription
+    where
+        L: AsBatchListener<State, Event>,
+        E: IntoIterator<Item = Event>,
+        State: 'static,
+        Event: Hash + Eq + 'static,
+    {
+        self.add_listener(
+            ListenerKind::Batch(listener.as_batch_listener()),
+            EventFilter::events(HashSet::from_iter(events)),
+            None,
+            0,
+        )
+    }
+
+    /// Subscribe to changes in a value derived from the store state
+    /// using the `selector` function. The `callback` is invoked with
+    /// the new state and the newly selected value, only when the
+    /// selected value changes (compared using [PartialEq]) after the
+    /// store listeners are notified. The selector is checked every
+    /// time the store's state is replaced, regardless of whether any
+    /// events were produced, or of the store's [NotifyPolicy].
+    ///
+    /// The selected value is memoized (see [Selector]), so the
+    /// `selector` is only re-computed when the state has been
+    /// replaced. The initial value is computed from the store's
+    /// current state when this method is called.
+    ///
+    /// The store holds onto the `callback` until the returned
+    /// [Subscription] is dropped.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # use reactive_state::{ReducerFn, ReducerResult, Store};
+    /// # use std::{cell::Cell, rc::Rc};
+    /// struct State {
+    ///     name: String,
+    ///     clicks: u32,
+    /// }
+    ///
+    /// let reducer: ReducerFn<State, u32, (), ()> = |state, clicks| ReducerResult {
+    ///     state: Rc::new(State {
+    ///         name: state.name.clone(),
+    ///         clicks: *clicks,
+    ///     }),
+    ///     events: vec![()],
+    ///     effects: vec![],
+    /// };
+    /// let store = Store::new(reducer, State { name: "Luke".to_string(), clicks: 0 });
+    ///
+    /// let renders = Rc::new(Cell::new(0));
+    /// let renders_local = renders.clone();
+    /// let _subscription = store.subscribe_selector(
+    ///     |state| state.clicks > 1,
+    ///     move |_state, _many_clicks| renders_local.set(renders_local.get() + 1),
+    /// );
+    ///
+    /// store.dispatch(1u32);
+    /// assert_eq!(0, renders.get());
+    /// store.dispatch(2u32);
+    /// assert_eq!(1, renders.get());
+    /// store.dispatch(3u32);
+    /// assert_eq!(1, renders.get());
+    /// ```
+    pub fn subscribe_selector<Output, S, C>(&self, selector: S, callback: C) -> Subscription
+    where
+        State: 'static,
+        Event: 'static,
+        Output: Clone + PartialEq + 'static,
+        S: Fn(&State) -> Output + 'static,
+        C: Fn(Rc<State>, &Output) + 'static,
+    {
+        let selector = Selector::new(selector);
+        let previous = RefCell::new(selector.select(&self.state()));
+
+        let callback = Callback::new(move |state: Rc<State>, _event: Option<Event>| {
+            let output = selector.select(&state);
+            if *previous.borrow() != output {
+                callback(state, &output);
+                *previous.borrow_mut() = output;
+            }
+        });
+
+        let id = self.listeners.add_callback(
+            callback,
+            EventFilter::All,
+            Some(NotifyPolicy::OnStateChange),
+        );
+        self.subscription(id)
+    }
+
+    /// Select a part of the store's current state using the
+    /// `selector` projection, returning a clone of it.
+    ///
+    /// ```
+    /// # use reactive_state::{ReducerFn, ReducerResult, Store};
+    /// # use std::rc::Rc;
+    /// struct User {
+    ///     name: String,
+    /// }
+    ///
+    /// struct State {
+    ///     user: User,
+    /// }
+    ///
+    /// # let reducer: ReducerFn<State, (), (), ()> = |state, _| ReducerResult {
+    /// #     state: state.clone(),
+    /// #     events: vec![],
+    /// #     effects: vec![],
+    /// # };
+    /// let store = Store::new(reducer, State { user: User { name: "Luke".to_string() } });
+    /// assert_eq!("Luke", store.select(|s| &s.user.name));
+    /// ```
+    ///
+    /// A projection only borrows from the state, so there is nothing
+    /// to memoize. To compute a derived value which is memoized
+    /// against the previous state, use
+    /// [select_with()](Store::select_with()).
+    pub fn select<Output, S>(&self, selector: S) -> Output
+    where
+        Output: Clone,
+        S: Fn(&State) -> &Output,
+    {
+        selector(&self.state.borrow()).clone()
+    }
+
+    /// Compute a value derived from the store's current state using
+    /// the `selector`. The value is only re-computed when the store's
+    /// state has been replaced since the last time this `selector`
+    /// was used (see [Selector]).
+    pub fn select_with<Output: Clone>(&self, selector: &Selector<State, Output>) -> Output {
+        selector.select(&self.state())
+    }
+
+    /// Remove the listener associated with the [Subscription] that
+    /// has the specified `id`. Returns `true` if the listener was
+    /// still subscribed to this store.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.listeners.unsubscribe(id) || self.error_listeners.unsubscribe(id)
+    }
+
+    /// Subscribe a `callback` which is invoked when the store's
+    /// [Reducer] rejects an `Action`, see
+    /// [new_fallible()](Store::new_fallible()). The callback remains
+    /// subscribed until the returned [Subscription] is dropped.
+    pub fn subscribe_errors<F: Fn(&ReduceError) + 'static>(&self, callback: F) -> Subscription {
+        let id = self.listeners.next_id();
+        self.error_listeners.add(id, Rc::new(callback));
+        let registry: Rc<dyn Unsubscribe> = self.error_listeners.clone();
+        Subscription::new(id, Rc::downgrade(&registry))
+    }
+
+    /// The number of listeners which are currently subscribed to this
+    /// store.
+    pub fn listener_count(&self) -> usize {
+        self.listeners.len()
+    }
+
+    /// Add [Middleware] to modify the behaviour of this [Store]
+    /// during a [dispatch()](Store::dispatch()). The middleware is
+    /// added after all the middleware which has already been added.
+    ///
+    /// The returned [MiddlewareId] can be used to remove the
+    /// middleware again with
+    /// [remove_middleware()](Store::remove_middleware()), or to
+    /// position other middleware relative to it with
+    /// [insert_middleware()](Store::insert_middleware()).
+    pub fn add_middleware<M: Middleware<State, Action, Event, Effect> + 'static>(
+        &self,
+        middleware: M,
+    ) -> MiddlewareId {
+        self.insert_middleware(middleware, MiddlewarePosition::Last)
+    }
+
+    /// Add [Middleware] to modify the behaviour of this [Store]
+    /// during a [dispatch()](Store::dispatch()), at the specified
+    /// `position` in the middleware chain.
+    ///
+    /// As with [add_middleware()](Store::add_middleware()), the
+    /// middleware only takes effect from the start of the next
+    /// `Action` to be dispatched.
+    pub fn insert_middleware<M: Middleware<State, Action, Event, Effect> + 'static>(
+        &self,
+        middleware: M,
+        position: MiddlewarePosition,
+    ) -> MiddlewareId {
+        let id = MiddlewareId::new(self.next_middleware_id.get());
+        self.next_middleware_id
+            .set(self.next_middleware_id.get() + 1);
+
+        self.modification_queue
+            .borrow_mut()
+            .push_back(StoreModification::AddMiddleware {
+                entry: MiddlewareEntry {
+                    id,
+                    middleware: Rc::new(middleware),
+                },
+                position,
+            });
+
+        id
+    }
+
+    /// Remove the [Middleware] with the specified `id` (returned by
+    /// [add_middleware()](Store::add_middleware()) or
+    /// [insert_middleware()](Store::insert_middleware())) from this
+    /// store. The middleware is removed before the next `Action` is
+    /// dispatched.
+    pub fn remove_middleware(&self, id: MiddlewareId) {
+        self.modification_queue
+            .borrow_mut()
+            .push_back(StoreModification::RemoveMiddleware(id));
+    }
+}
+
+/// Subscriptions which match events with a predicate rather than by
+/// comparing them, so they don't need any `Event` bounds.
+impl<State, Action, Event, Effect> Store<State, Action, Event, Effect> {
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// being dispatched via [dispatch()](Store::dispatch()). This
+    /// subscription is only active for events for which the
+    /// `predicate` returns `true`, and the listener is invoked once
+    /// for each of them.
+    ///
+    /// This can be used to subscribe to events carrying a payload,
+    /// such as `ItemChanged(id)` for a particular range of ids, see
+    /// also [subscribe_discriminants()](Store::subscribe_discriminants()).
+    ///
+    /// The listener remains subscribed until the returned
+    /// [Subscription] is dropped, or the strong reference associated
+    /// with it (usually [Callback](crate::Callback)) is dropped.
+    pub fn subscribe_filter<L, P>(&self, listener: L, predicate: P) -> Subscription
+    where
+        L: AsListener<State, Event>,
+        P: Fn(&Event) -> bool + 'static,
+        State: 'static,
+        Event: 'static,
+    {
+        self.add_listener(
+            ListenerKind::Single(listener.as_listener()),
+            EventFilter::Predicate(Box::new(predicate)),
+            None,
+            0,
+        )
+    }
+
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// being dispatched via [dispatch()](Store::dispatch()). This
+    /// subscription is only active for events which are the same enum
+    /// variant as any of the `events` (compared using
+    /// [std::mem::discriminant()]), regardless of their payload. For
+    /// example, subscribing with `[ItemChanged(0)]` matches
+    /// `ItemChanged(id)` for any id.
+    ///
+    /// The listener remains subscribed until the returned
+    /// [Subscription] is dropped, or the strong reference associated
+    /// with it (usually [Callback](crate::Callback)) is dropped.
+    pub fn subscribe_discriminants<L, E>(&self, listener: L, events: E) -> Subscription
+    where
+        L: AsListener<State, Event>,
+        E: IntoIterator<Item = Event>,
+        State: 'static,
+        Event: 'static,
+    {
+        let discriminants: HashSet<Discriminant<Event>> = events
+            .into_iter()
+            .map(|event| mem::discriminant(&event))
+            .collect();
+        self.subscribe_filter(listener, move |event| {
+            discriminants.contains(&mem::discriminant(event))
+        })
+    }
+
+    /// Subscribe a [BatchListener](crate::BatchListener) in the same
+    /// way as [subscribe_batch()](Store::subscribe_batch()), but
+    /// matching the events for which the `predicate` returns `true`,
+    /// see [subscribe_filter()](Store::subscribe_filter()).
+    pub fn subscribe_batch_filter<L, P>(&self, listener: L, predicate: P) -> Subscription
+    where
+        L: AsBatchListener<State, Event>,
+        P: Fn(&Event) -> bool + 'static,
+        State: 'static,
+        Event: 'static,
+    {
+        self.add_listener(
+            ListenerKind::Batch(listener.as_batch_listener()),
+            EventFilter::Predicate(Box::new(predicate)),
+            None,
+            0,
+        )
+    }
+
+    /// Add a listener to [Store::listeners], returning the
+    /// [Subscription] which removes it again.
+    fn add_listener(
+        &self,
+        listener: ListenerKind<State, Event>,
+        filter: EventFilter<Event>,
+        policy: Option<NotifyPolicy>,
+        priority: i32,
+    ) -> Subscription
+    where
+        State: 'static,
+        Event: 'static,
+    {
+        let id = self.listeners.add(listener, filter, policy, priority);
+        self.subscription(id)
+    }
+
+    /// Create the [Subscription] for the listener with the specified
+    /// `id`.
+    fn subscription(&self, id: SubscriptionId) -> Subscription
+    where
+        State: 'static,
+        Event: 'static,
+    {
+        let registry: Rc<dyn Unsubscribe> = self.listeners.clone();
+        Subscription::new(id, Rc::downgrade(&registry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        middleware::{
+            Middleware, MiddlewarePosition, NotifyNext, ReduceMiddlewareResult, ReduceNext,
+        },
+        BatchCallback, Callback, DispatchFuture, DispatchOutcome, NotifyPolicy, PanicSource,
+        ReduceError, Reducer, ReducerFn, ReducerResult, Selector, Store, StoreRef, Subscription,
+        TransactionError, TryReducerFn,
+    };
+    use std::{
+        cell::{Cell, RefCell},
+        future::Future,
+        pin::Pin,
+        rc::Rc,
+        task::{Context, Poll, Waker},
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct TestState {
+        counter: i32,
     }
 
     #[derive(Copy, Clone)]
@@ -521,6 +1765,19 @@ mod tests {
                 effects,
             }
         }
+
+        fn try_reduce(
+            &self,
+            state: &Rc<TestState>,
+            action: &TestAction,
+        ) -> Result<ReducerResult<TestState, TestEvent, TestEffect>, ReduceError> {
+            let result = self.reduce(state, action);
+            if result.state.counter < 0 {
+                Err(ReduceError::new("counter cannot be negative"))
+            } else {
+                Ok(result)
+            }
+        }
     }
 
     struct TestReduceMiddleware {
@@ -530,11 +1787,11 @@ mod tests {
     impl Middleware<TestState, TestAction, TestEvent, TestEffect> for TestReduceMiddleware {
         fn on_reduce(
             &self,
-            store: &Store<TestState, TestAction, TestEvent, TestEffect>,
+            _store: &Store<TestState, TestAction, TestEvent, TestEffect>,
             action: Option<&TestAction>,
-            reduce: crate::middleware::ReduceFn<TestState, TestAction, TestEvent, TestEffect>,
+            next: ReduceNext<'_, TestState, TestAction, TestEvent, TestEffect>,
         ) -> ReduceMiddlewareResult<TestEvent, TestEffect> {
-            reduce(store, action.map(|_| &self.new_action))
+            next.reduce(action.map(|_| &self.new_action))
         }
     }
 
@@ -575,7 +1832,7 @@ mod tests {
                 *callback_test_copy.borrow_mut() = state.counter;
             });
 
-        store.borrow_mut().subscribe(&callback);
+        let _subscription = store.borrow_mut().subscribe(&callback);
 
         assert_eq!(0, store.borrow().state().counter);
 
@@ -600,7 +1857,7 @@ mod tests {
                 *callback_test_copy.borrow_mut() = state.counter;
             });
 
-        store.subscribe(&callback);
+        let _subscription = store.subscribe(&callback);
         store.add_middleware(TestReduceMiddleware {
             new_action: TestAction::Decrement,
         });
@@ -624,7 +1881,7 @@ mod tests {
                 *callback_test_copy.borrow_mut() = state.counter;
             });
 
-        store.subscribe(&callback);
+        let _subscription = store.subscribe(&callback);
         store.add_middleware(TestReduceMiddleware {
             new_action: TestAction::Decrement2,
         });
@@ -636,6 +1893,59 @@ mod tests {
         assert_eq!(-1, *callback_test.borrow());
     }
 
+    #[test]
+    fn test_remove_middleware() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let id = store.add_middleware(TestReduceMiddleware {
+            new_action: TestAction::Decrement,
+        });
+        store.dispatch(TestAction::Increment);
+        assert_eq!(-1, store.state().counter);
+
+        store.remove_middleware(id);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(0, store.state().counter);
+    }
+
+    #[test]
+    fn test_insert_middleware() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        // The last middleware in the chain determines the action
+        // which reaches the reducer.
+        let decrement2 = store.add_middleware(TestReduceMiddleware {
+            new_action: TestAction::Decrement2,
+        });
+        let decrement = store.insert_middleware(
+            TestReduceMiddleware {
+                new_action: TestAction::Decrement,
+            },
+            MiddlewarePosition::Before(decrement2),
+        );
+        store.dispatch(TestAction::Increment);
+        assert_eq!(-2, store.state().counter);
+
+        store.insert_middleware(
+            TestReduceMiddleware {
+                new_action: TestAction::Increment,
+            },
+            MiddlewarePosition::First,
+        );
+        store.remove_middleware(decrement2);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(-3, store.state().counter);
+
+        store.insert_middleware(
+            TestReduceMiddleware {
+                new_action: TestAction::Increment,
+            },
+            MiddlewarePosition::After(decrement),
+        );
+        store.dispatch(TestAction::Decrement);
+        assert_eq!(-2, store.state().counter);
+    }
+
     #[test]
     fn test_effect_middleware() {
         let initial_state = TestState { counter: 0 };
@@ -661,13 +1971,224 @@ mod tests {
                 *callback_test_copy.borrow_mut() = Some(TestEvent::CounterIsZero);
             });
 
-        store.subscribe_event(&callback_zero_subscription, TestEvent::CounterIsZero);
+        let _subscription =
+            store.subscribe_event(&callback_zero_subscription, TestEvent::CounterIsZero);
         store.dispatch(TestAction::Increment);
         assert_eq!(None, *callback_test.borrow());
         store.dispatch(TestAction::Increment);
         assert_eq!(Some(TestEvent::CounterIsZero), *callback_test.borrow());
     }
 
+    #[test]
+    fn test_dispatch_async() {
+        let store = StoreRef::new(TestReducer, TestState { counter: -1 });
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut future = store.dispatch_async(TestAction::Increment);
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(DispatchOutcome::Completed { state, events }) => {
+                assert_eq!(0, state.counter);
+                assert_eq!(
+                    vec![TestEvent::CounterChanged, TestEvent::CounterIsZero],
+                    events
+                );
+            }
+            _ => panic!("expected the dispatch to be completed"),
+        }
+    }
+
+    /// Dispatch asynchronously from within a listener, while another
+    /// dispatch is in progress.
+    #[test]
+    fn test_dispatch_async_reentrant() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let future: Rc<RefCell<Option<DispatchFuture<TestState, TestEvent>>>> =
+            Rc::new(RefCell::new(None));
+        let future_local = future.clone();
+        let store_local = store.clone();
+        let callback: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            if future_local.borrow().is_some() {
+                return;
+            }
+
+            let mut future = store_local.dispatch_async(TestAction::Decrement2);
+            // The outer dispatch is still in progress.
+            let mut cx = Context::from_waker(Waker::noop());
+            assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+            assert_eq!(1, store_local.state().counter);
+            *future_local.borrow_mut() = Some(future);
+        });
+        let _subscription = store.subscribe_event(&callback, TestEvent::CounterChanged);
+
+        store.dispatch(TestAction::Increment);
+
+        let mut future = future.borrow_mut().take().unwrap();
+        assert!(future.is_complete());
+        let mut cx = Context::from_waker(Waker::noop());
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(DispatchOutcome::Completed { state, .. }) => {
+                assert_eq!(-1, state.counter);
+            }
+            _ => panic!("expected the dispatch to be completed"),
+        }
+    }
+
+    #[test]
+    fn test_subscription_drop() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let callback_test = Rc::new(RefCell::new(0));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            *callback_test_copy.borrow_mut() += 1;
+        });
+
+        let subscription = store.subscribe(&callback);
+        let subscription_event = store.subscribe_event(&callback, TestEvent::CounterChanged);
+        assert_eq!(2, store.listener_count());
+        assert_ne!(subscription.id(), subscription_event.id());
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(2, *callback_test.borrow());
+
+        drop(subscription);
+        assert_eq!(1, store.listener_count());
+        store.dispatch(TestAction::Increment);
+        assert_eq!(3, *callback_test.borrow());
+
+        subscription_event.unsubscribe();
+        assert_eq!(0, store.listener_count());
+        store.dispatch(TestAction::Increment);
+        assert_eq!(3, *callback_test.borrow());
+    }
+
+    #[test]
+    fn test_unsubscribe_id() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let callback_test = Rc::new(RefCell::new(0));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            *callback_test_copy.borrow_mut() += 1;
+        });
+
+        let id = store.subscribe(&callback).detach();
+        store.dispatch(TestAction::Increment);
+        assert_eq!(1, *callback_test.borrow());
+
+        assert!(store.unsubscribe(id));
+        assert!(!store.unsubscribe(id));
+        store.dispatch(TestAction::Increment);
+        assert_eq!(1, *callback_test.borrow());
+    }
+
+    /// A listener which unsubscribes another listener while the
+    /// store is notifying its listeners.
+    #[test]
+    fn test_unsubscribe_during_notify() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let subscription: Rc<RefCell<Option<Subscription>>> = Rc::new(RefCell::new(None));
+        let subscription_local = subscription.clone();
+        let callback1: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            subscription_local.borrow_mut().take();
+        });
+
+        let callback_test = Rc::new(RefCell::new(0));
+        let callback_test_copy = callback_test.clone();
+        let callback2: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            *callback_test_copy.borrow_mut() += 1;
+        });
+
+        let _subscription1 = store.subscribe(&callback1);
+        *subscription.borrow_mut() = Some(store.subscribe(&callback2));
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(0, *callback_test.borrow());
+        assert_eq!(1, store.listener_count());
+    }
+
+    #[test]
+    fn test_subscribe_selector() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let callback_test: Rc<RefCell<Vec<bool>>> = Rc::new(RefCell::new(Vec::new()));
+        let callback_test_copy = callback_test.clone();
+        let subscription = store.subscribe_selector(
+            |state| state.counter > 1,
+            move |state, greater_than_one| {
+                assert_eq!(*greater_than_one, state.counter > 1);
+                callback_test_copy.borrow_mut().push(*greater_than_one);
+            },
+        );
+        assert_eq!(1, store.listener_count());
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Decrement2);
+        store.dispatch(TestAction::Decrement);
+        assert_eq!(vec![true, false], *callback_test.borrow());
+        assert_eq!(0, store.select(|state| &state.counter));
+
+        // The selector is checked when the state is replaced without
+        // producing any events.
+        store.replace_state(Rc::new(TestState { counter: 2 }), vec![]);
+        assert_eq!(vec![true, false, true], *callback_test.borrow());
+
+        let calls = Rc::new(Cell::new(0));
+        let calls_copy = calls.clone();
+        let selector = Selector::new(move |state: &TestState| {
+            calls_copy.set(calls_copy.get() + 1);
+            state.counter * 2
+        });
+        assert_eq!(4, store.select_with(&selector));
+        assert_eq!(4, store.select_with(&selector));
+        assert_eq!(1, calls.get());
+
+        drop(subscription);
+        assert_eq!(0, store.listener_count());
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(3, callback_test.borrow().len());
+    }
+
+    #[test]
+    fn test_replace_state() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let callback_test: Rc<RefCell<Vec<Option<TestEvent>>>> = Rc::new(RefCell::new(Vec::new()));
+        let callback_test_copy = callback_test.clone();
+        let store_copy = store.clone();
+        let callback = Callback::new(move |state: Rc<TestState>, event| {
+            callback_test_copy.borrow_mut().push(event);
+            // Replacing the state during a notification is queued.
+            if state.counter == 5 {
+                store_copy.replace_state(Rc::new(TestState { counter: 0 }), vec![]);
+                assert_eq!(5, store_copy.state().counter);
+            }
+        });
+        let _subscription = store.subscribe_event(&callback, TestEvent::CounterChanged);
+
+        store.replace_state(
+            Rc::new(TestState { counter: 5 }),
+            vec![TestEvent::CounterChanged],
+        );
+        assert_eq!(0, store.state().counter);
+        assert_eq!(
+            vec![Some(TestEvent::CounterChanged)],
+            *callback_test.borrow()
+        );
+
+        // Using the reducer directly does not alter the store.
+        let result = store
+            .reducer()
+            .reduce(&store.state(), &TestAction::Increment);
+        assert_eq!(1, result.state.counter);
+        assert_eq!(0, store.state().counter);
+    }
+
     /// Subscribe to an action that produces no events.
     #[test]
     fn test_subscribe_no_event() {
@@ -683,7 +2204,7 @@ mod tests {
                 *callback_test_copy.borrow_mut() = state.counter;
             });
 
-        store.subscribe(&callback);
+        let _subscription = store.subscribe(&callback);
 
         assert_eq!(0, store.state.borrow().counter);
         assert_eq!(0, *callback_test.borrow());
@@ -696,4 +2217,976 @@ mod tests {
         // because the action produced no events.
         assert_eq!(0, *callback_test.borrow());
     }
+
+    #[test]
+    fn test_notify_policy() {
+        // 0 leaves the state untouched, 1 replaces it without
+        // producing events, and 2 replaces it producing an event.
+        let reducer: ReducerFn<i32, i32, i32, ()> = |state, action| ReducerResult {
+            state: match action {
+                0 => state.clone(),
+                _ => Rc::new(**state + action),
+            },
+            events: if *action == 2 { vec![*action] } else { vec![] },
+            effects: vec![],
+        };
+        let store = Store::new(reducer, 0);
+
+        let counts = Rc::new(RefCell::new([0; 3]));
+        let counter = |index: usize| {
+            let counts = counts.clone();
+            Callback::new(move |_, _| counts.borrow_mut()[index] += 1)
+        };
+        let on_events: Callback<i32, i32> = counter(0);
+        let on_state_change: Callback<i32, i32> = counter(1);
+        let always: Callback<i32, i32> = counter(2);
+
+        let _on_events = store.subscribe(&on_events);
+        let _on_state_change =
+            store.subscribe_with_policy(&on_state_change, NotifyPolicy::OnStateChange);
+        let _always = store.subscribe_with_policy(&always, NotifyPolicy::Always);
+
+        store.dispatch(0);
+        assert_eq!([0, 0, 1], *counts.borrow());
+        store.dispatch(1);
+        assert_eq!([0, 1, 2], *counts.borrow());
+        store.dispatch(2);
+        assert_eq!([1, 2, 3], *counts.borrow());
+
+        store.set_notify_policy(NotifyPolicy::Always);
+        assert_eq!(NotifyPolicy::Always, store.notify_policy());
+        store.dispatch(0);
+        assert_eq!([2, 2, 4], *counts.borrow());
+    }
+
+    #[test]
+    fn test_listener_priority() {
+        let initial_state = TestState { counter: 0 };
+        let store = StoreRef::new(TestReducer, initial_state);
+
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let recorder = |name: &'static str| {
+            let order = order.clone();
+            Callback::new(move |_, _| order.borrow_mut().push(name))
+        };
+        let view: Callback<TestState, TestEvent> = recorder("view");
+        let dead: Callback<TestState, TestEvent> = recorder("dead");
+        let model: Callback<TestState, TestEvent> = recorder("model");
+        let view2: Callback<TestState, TestEvent> = recorder("view2");
+        let cache: Callback<TestState, TestEvent> = recorder("cache");
+
+        let _view = store.subscribe(&view);
+        store.subscribe(&dead).detach();
+        let _model = store.subscribe_with_priority(&model, 10);
+        let _view2 = store.subscribe(&view2);
+        let cache_subscription = store.subscribe_with_priority(&cache, -1);
+
+        drop(dead);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(vec!["model", "view", "view2", "cache"], *order.borrow());
+
+        order.borrow_mut().clear();
+        assert!(store.set_listener_priority(cache_subscription.id(), 10));
+        store.dispatch(TestAction::Increment);
+        assert_eq!(vec!["model", "cache", "view", "view2"], *order.borrow());
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum ItemEvent {
+        Changed(u32),
+        Removed(u32),
+    }
+
+    /// Generic over the `Event` without requiring any bounds on it.
+    fn subscribe_matching<State: 'static, Action, Event: 'static, Effect>(
+        store: &Store<State, Action, Event, Effect>,
+        callback: &Callback<State, Event>,
+        predicate: fn(&Event) -> bool,
+    ) -> Subscription {
+        store.subscribe_filter(callback, predicate)
+    }
+
+    #[test]
+    fn test_subscribe_filter_and_discriminants() {
+        let reducer: ReducerFn<(), ItemEvent, ItemEvent, ()> = |state, action| ReducerResult {
+            state: state.clone(),
+            events: vec![action.clone()],
+            effects: vec![],
+        };
+        let store = Store::new(reducer, ());
+
+        let changed = Rc::new(RefCell::new(Vec::new()));
+        let changed_copy = changed.clone();
+        let changed_callback: Callback<(), ItemEvent> = Callback::new(move |_, event| {
+            changed_copy.borrow_mut().push(event.unwrap());
+        });
+        let removed = Rc::new(RefCell::new(Vec::new()));
+        let removed_copy = removed.clone();
+        let removed_callback: Callback<(), ItemEvent> = Callback::new(move |_, event| {
+            removed_copy.borrow_mut().push(event.unwrap());
+        });
+
+        let _changed =
+            store.subscribe_discriminants(&changed_callback, vec![ItemEvent::Changed(0)]);
+        let _removed = subscribe_matching(
+            &store,
+            &removed_callback,
+            |event| matches!(event, ItemEvent::Removed(id) if *id > 1),
+        );
+
+        for id in 1..=2 {
+            store.dispatch(ItemEvent::Changed(id));
+            store.dispatch(ItemEvent::Removed(id));
+        }
+
+        assert_eq!(
+            vec![ItemEvent::Changed(1), ItemEvent::Changed(2)],
+            *changed.borrow()
+        );
+        assert_eq!(vec![ItemEvent::Removed(2)], *removed.borrow());
+    }
+
+    /// Neither [Hash](std::hash::Hash) nor [Eq].
+    #[derive(Debug, Clone, PartialEq)]
+    enum MeasureEvent {
+        Changed(f64),
+        Reset,
+    }
+
+    #[test]
+    fn test_non_hash_event() {
+        let reducer: ReducerFn<f64, f64, MeasureEvent, ()> = |state, action| {
+            let events = if *action == 0.0 {
+                vec![MeasureEvent::Reset]
+            } else {
+                vec![MeasureEvent::Changed(*action)]
+            };
+            ReducerResult {
+                state: Rc::new(if *action == 0.0 {
+                    0.0
+                } else {
+                    **state + action
+                }),
+                events,
+                effects: vec![],
+            }
+        };
+        let store = Store::new(reducer, 0.0);
+
+        let all = Rc::new(RefCell::new(Vec::new()));
+        let all_copy = all.clone();
+        let all_callback: Callback<f64, MeasureEvent> = Callback::new(move |state, event| {
+            all_copy.borrow_mut().push((*state, event));
+        });
+        let _all = store.subscribe(&all_callback);
+
+        let large = Rc::new(RefCell::new(Vec::new()));
+        let large_copy = large.clone();
+        let large_callback: Callback<f64, MeasureEvent> = Callback::new(move |_, event| {
+            large_copy.borrow_mut().push(event.unwrap());
+        });
+        let _large = store.subscribe_filter(
+            &large_callback,
+            |event: &MeasureEvent| matches!(event, MeasureEvent::Changed(value) if *value > 1.0),
+        );
+
+        store.dispatch(0.5);
+        store.batch(|store| {
+            store.dispatch(2.5);
+            store.dispatch(0.0);
+        });
+
+        assert_eq!(vec![(0.5, None), (0.0, None)], *all.borrow());
+        assert_eq!(vec![MeasureEvent::Changed(2.5)], *large.borrow());
+    }
+
+    #[test]
+    fn test_subscribe_batch() {
+        let initial_state = TestState { counter: 1 };
+        let store = StoreRef::new(TestReducer, initial_state);
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let calls_copy = calls.clone();
+        let callback: BatchCallback<TestState, TestEvent> =
+            BatchCallback::new(move |state: Rc<TestState>, events: &[TestEvent]| {
+                calls_copy
+                    .borrow_mut()
+                    .push((state.counter, events.to_vec()));
+            });
+        let all_calls = Rc::new(RefCell::new(0));
+        let all_calls_copy = all_calls.clone();
+        let all_callback: BatchCallback<TestState, TestEvent> =
+            BatchCallback::new(move |_, _| *all_calls_copy.borrow_mut() += 1);
+
+        let _subscription = store.subscribe_batch(
+            &callback,
+            vec![TestEvent::CounterChanged, TestEvent::CounterIsZero],
+        );
+        let _all_subscription = store.subscribe_batch(&all_callback, vec![]);
+
+        store.dispatch(TestAction::Decrement);
+        store.dispatch(TestAction::NoEvent);
+        store.dispatch(TestAction::Increment);
+
+        assert_eq!(
+            vec![
+                (0, vec![TestEvent::CounterChanged, TestEvent::CounterIsZero]),
+                (43, vec![TestEvent::CounterChanged]),
+            ],
+            *calls.borrow()
+        );
+        assert_eq!(2, *all_calls.borrow());
+    }
+
+    #[test]
+    fn test_dispatch_batch() {
+        let initial_state = TestState { counter: 0 };
+        let store = StoreRef::new(TestReducer, initial_state);
+
+        let callback_test = Rc::new(RefCell::new(Vec::new()));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> =
+            Callback::new(move |state: Rc<TestState>, event| {
+                callback_test_copy.borrow_mut().push((state.counter, event));
+            });
+
+        let _subscription = store.subscribe_events(
+            &callback,
+            vec![TestEvent::CounterChanged, TestEvent::CounterIsZero],
+        );
+
+        store.add_middleware(TestEffectMiddleware);
+        store.dispatch_batch(vec![
+            TestAction::Increment,
+            TestAction::Increment,
+            TestAction::Decrent2Then1,
+        ]);
+
+        // The effect produced by the last action in the batch
+        // dispatches another action after the batch. The events from
+        // every action in the batch are kept.
+        assert_eq!(-1, store.state().counter);
+        assert_eq!(
+            vec![
+                (0, Some(TestEvent::CounterChanged)),
+                (0, Some(TestEvent::CounterChanged)),
+                (0, Some(TestEvent::CounterChanged)),
+                (0, Some(TestEvent::CounterIsZero)),
+                (-1, Some(TestEvent::CounterChanged)),
+            ],
+            *callback_test.borrow()
+        );
+    }
+
+    #[test]
+    fn test_batch() {
+        let initial_state = TestState { counter: 0 };
+        let store = StoreRef::new(TestReducer, initial_state);
+
+        let callback_test: Rc<RefCell<Vec<i32>>> = Rc::new(RefCell::new(Vec::new()));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> =
+            Callback::new(move |state: Rc<TestState>, _event| {
+                callback_test_copy.borrow_mut().push(state.counter);
+            });
+
+        let _subscription = store.subscribe_event(&callback, TestEvent::CounterChanged);
+
+        let mut future = store.batch(|store| {
+            store.dispatch(TestAction::Increment);
+            let future = store.dispatch_async(TestAction::Increment);
+            store.batch(|store| store.dispatch_batch(vec![TestAction::Increment; 3]));
+            assert_eq!(0, store.state().counter);
+            future
+        });
+
+        assert_eq!(5, store.state().counter);
+        assert_eq!(vec![5; 5], *callback_test.borrow());
+
+        let mut cx = Context::from_waker(Waker::noop());
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(DispatchOutcome::Completed { state, events }) => {
+                assert_eq!(5, state.counter);
+                assert_eq!(vec![TestEvent::CounterChanged; 5], events);
+            }
+            _ => panic!("expected the batch to have completed"),
+        }
+    }
+
+    #[test]
+    fn test_batch_panic() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let mut future = None;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            store.batch(|store| {
+                store.batch(|store| store.dispatch(TestAction::Increment));
+                future = Some(store.dispatch_async(TestAction::Increment));
+                panic!("batch failed");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(0, store.state().counter);
+
+        let mut cx = Context::from_waker(Waker::noop());
+        match Pin::new(&mut future.unwrap()).poll(&mut cx) {
+            Poll::Ready(DispatchOutcome::Cancelled) => {}
+            _ => panic!("expected the batch to have been cancelled"),
+        }
+
+        // Dispatching is no longer stuck in the batch.
+        store.dispatch(TestAction::Increment);
+        assert_eq!(1, store.state().counter);
+    }
+
+    #[test]
+    fn test_transaction_commit() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let callback_test = Rc::new(RefCell::new(Vec::new()));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> =
+            Callback::new(move |state: Rc<TestState>, event| {
+                callback_test_copy.borrow_mut().push((state.counter, event));
+            });
+        let _subscription = store.subscribe_events(
+            &callback,
+            vec![TestEvent::CounterChanged, TestEvent::CounterIsZero],
+        );
+        store.add_middleware(TestEffectMiddleware);
+
+        let result: Result<i32, TransactionError<()>> = store.transaction(|tx| {
+            tx.dispatch(TestAction::Increment);
+            tx.dispatch(TestAction::Increment);
+            tx.dispatch(TestAction::Increment);
+            tx.dispatch(TestAction::Decrent2Then1);
+            assert_eq!(0, store.state().counter);
+            Ok(tx.state().counter)
+        });
+
+        assert_eq!(1, result.unwrap());
+        // The effect from the transaction dispatches a decrement once
+        // it has been committed.
+        assert_eq!(0, store.state().counter);
+        assert_eq!(
+            vec![
+                (1, Some(TestEvent::CounterChanged)),
+                (1, Some(TestEvent::CounterChanged)),
+                (1, Some(TestEvent::CounterChanged)),
+                (1, Some(TestEvent::CounterChanged)),
+                (0, Some(TestEvent::CounterChanged)),
+                (0, Some(TestEvent::CounterIsZero)),
+            ],
+            *callback_test.borrow()
+        );
+    }
+
+    #[test]
+    fn test_transaction_rollback() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 1 });
+        let initial_state = store.state();
+
+        let callback_test = Rc::new(RefCell::new(0));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            *callback_test_copy.borrow_mut() += 1;
+        });
+        let _subscription = store.subscribe(&callback);
+
+        let result = store.transaction(|tx| {
+            tx.dispatch(TestAction::Increment);
+            Err::<(), _>("cancelled")
+        });
+        assert!(matches!(
+            result,
+            Err(TransactionError::Aborted("cancelled"))
+        ));
+
+        let result: Result<(), TransactionError<()>> = store.transaction(|tx| {
+            assert!(tx.dispatch(TestAction::Decrement));
+            assert!(!tx.dispatch(TestAction::Decrement));
+            assert!(!tx.dispatch(TestAction::Increment));
+            assert!(tx.is_rejected());
+            Ok(())
+        });
+        assert!(matches!(result, Err(TransactionError::Rejected(_))));
+
+        assert!(Rc::ptr_eq(&initial_state, &store.state()));
+        assert_eq!(0, *callback_test.borrow());
+    }
+
+    #[test]
+    fn test_transaction_rebase() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 1 });
+        let errors = Rc::new(RefCell::new(0));
+        let errors_copy = errors.clone();
+        let _subscription = store.subscribe_errors(move |_| *errors_copy.borrow_mut() += 1);
+
+        // The transaction's actions are reduced again on top of the
+        // action queued ahead of the commit.
+        store.batch(|store| {
+            store.dispatch(TestAction::Increment);
+            let result: Result<(), TransactionError<()>> = store.transaction(|tx| {
+                tx.dispatch(TestAction::Increment);
+                Ok(())
+            });
+            assert!(result.is_ok());
+        });
+        assert_eq!(3, store.state().counter);
+        assert_eq!(0, *errors.borrow());
+
+        // Reduced again, the commit is rejected and rolled back.
+        store.batch(|store| {
+            store.dispatch(TestAction::Decrement2);
+            let result: Result<(), TransactionError<()>> = store.transaction(|tx| {
+                assert!(tx.dispatch(TestAction::Decrement2));
+                Ok(())
+            });
+            assert!(result.is_ok());
+        });
+        assert_eq!(1, store.state().counter);
+        assert_eq!(1, *errors.borrow());
+    }
+
+    #[test]
+    fn test_transaction_middleware() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 5 });
+        store.add_middleware(TestReduceMiddleware {
+            new_action: TestAction::Decrement,
+        });
+
+        // The committed actions are sent through the middleware.
+        let result: Result<(), TransactionError<()>> = store.transaction(|tx| {
+            tx.dispatch(TestAction::Increment);
+            tx.dispatch(TestAction::Increment);
+            assert_eq!(7, tx.state().counter);
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(3, store.state().counter);
+
+        // Rejected by the reducer via the middleware, the whole commit
+        // is rolled back.
+        let state = store.state();
+        let result: Result<(), TransactionError<()>> = store.transaction(|tx| {
+            for _ in 0..4 {
+                tx.dispatch(TestAction::Increment);
+            }
+            Ok(())
+        });
+        assert!(matches!(result, Err(TransactionError::Rejected(_))));
+        assert!(Rc::ptr_eq(&state, &store.state()));
+    }
+
+    #[test]
+    fn test_dispatch_report() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        // Without any middleware to process it, the effect is not
+        // consumed.
+        let report = store.dispatch(TestAction::Decrent2Then1);
+        assert!(!report.queued);
+        assert_eq!(vec![TestEvent::CounterChanged], report.events);
+        assert_eq!(1, report.effects_emitted);
+        assert!(matches!(
+            report.unconsumed_effects[..],
+            [TestEffect::ChainAction(TestAction::Decrement)]
+        ));
+        assert!(report.state_changed);
+        assert_eq!(1, report.actions_processed);
+
+        store.add_middleware(TestEffectMiddleware);
+        let report = store.dispatch(TestAction::Decrent2Then1);
+        assert_eq!(1, report.effects_emitted);
+        assert!(report.unconsumed_effects.is_empty());
+        assert_eq!(2, report.actions_processed);
+        assert_eq!(-5, store.state().counter);
+
+        let store_copy = store.clone();
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let reports_copy = reports.clone();
+        let callback: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            reports_copy
+                .borrow_mut()
+                .push(store_copy.dispatch(TestAction::NoEvent));
+        });
+        let _subscription = store.subscribe_event(&callback, TestEvent::CounterChanged);
+
+        let report = store.dispatch(TestAction::Increment);
+        assert_eq!(2, report.actions_processed);
+        assert!(reports.borrow()[0].queued);
+        assert_eq!(42, store.state().counter);
+    }
+
+    struct TestErrorMiddleware {
+        errors: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Middleware<i32, i32, (), ()> for TestErrorMiddleware {
+        fn on_error(&self, store: &Store<i32, i32, (), ()>, error: &ReduceError) {
+            assert_eq!(1, *store.state());
+            self.errors.borrow_mut().push(error.to_string());
+        }
+    }
+
+    #[test]
+    fn test_fallible_reducer() {
+        let reducer: TryReducerFn<i32, i32, (), (), &str> = TryReducerFn(|state, action| {
+            if **state + action < 0 {
+                return Err("negative");
+            }
+            Ok(ReducerResult {
+                state: Rc::new(**state + action),
+                events: vec![()],
+                effects: vec![],
+            })
+        });
+
+        let store = Store::new_fallible(reducer, 0);
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        store.add_middleware(TestErrorMiddleware {
+            errors: errors.clone(),
+        });
+
+        let listener_errors = Rc::new(RefCell::new(0));
+        let listener_errors_copy = listener_errors.clone();
+        let subscription = store.subscribe_errors(move |_| {
+            *listener_errors_copy.borrow_mut() += 1;
+        });
+
+        let report = store.dispatch(1);
+        assert!(report.error.is_none());
+        let state = store.state();
+
+        let report = store.dispatch(-2);
+        assert_eq!(
+            "action rejected by reducer: negative",
+            report.error.unwrap().to_string()
+        );
+        assert!(report.events.is_empty());
+        assert!(!report.state_changed);
+        assert!(Rc::ptr_eq(&state, &store.state()));
+        assert_eq!(
+            vec!["action rejected by reducer: negative".to_string()],
+            *errors.borrow()
+        );
+        assert_eq!(1, *listener_errors.borrow());
+
+        assert!(store.unsubscribe(subscription.detach()));
+        store.dispatch(-2);
+        assert_eq!(1, *listener_errors.borrow());
+        assert_eq!(2, errors.borrow().len());
+
+        // The future of a rejected action resolves with the error.
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut future = store.dispatch_async(-3);
+        assert_eq!(3, errors.borrow().len());
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(DispatchOutcome::Rejected(error)) => {
+                assert_eq!("action rejected by reducer: negative", error.to_string());
+            }
+            _ => panic!("expected the action to have been rejected"),
+        }
+
+        // Also when it is rejected within a batch, in which case the
+        // middleware is notified too.
+        let mut future = store.batch(|store| {
+            let future = store.dispatch_async(-2);
+            store.dispatch(1);
+            future
+        });
+        assert_eq!(2, *store.state());
+        assert_eq!(4, errors.borrow().len());
+        assert!(matches!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready(DispatchOutcome::Rejected(_))
+        ));
+
+        // A fallible reducer given to Store::new() still reports its
+        // rejections.
+        let store = Store::new(reducer, 0);
+        store.add_middleware(TestErrorMiddleware {
+            errors: errors.clone(),
+        });
+        store.dispatch(1);
+        let report = store.dispatch(-2);
+        assert!(report.error.is_some());
+        assert_eq!(1, *store.state());
+        assert_eq!(5, errors.borrow().len());
+    }
+
+    #[test]
+    fn test_panic_handler() {
+        let reducer: ReducerFn<i32, i32, i32, ()> = |state, action| {
+            if *action == 0 {
+                panic!("zero action");
+            }
+            ReducerResult {
+                state: Rc::new(**state + action),
+                events: vec![*action],
+                effects: vec![],
+            }
+        };
+        let store = StoreRef::new(reducer, 0);
+
+        let panics = Rc::new(RefCell::new(Vec::new()));
+        let panics_copy = panics.clone();
+        store.set_panic_handler(move |panic| {
+            panics_copy
+                .borrow_mut()
+                .push((panic.source, panic.message.clone().unwrap()));
+        });
+
+        let weak_store = store.downgrade();
+        let dispatcher: Callback<i32, i32> = Callback::new(move |_, event| {
+            if event == Some(1) {
+                let store = weak_store.upgrade().unwrap();
+                store.dispatch(0);
+                store.dispatch(10);
+            }
+        });
+        let panicker: Callback<i32, i32> = Callback::new(|state: Rc<i32>, _| {
+            if *state == 1 {
+                panic!("listener");
+            }
+        });
+        let calls = Rc::new(RefCell::new(0));
+        let calls_copy = calls.clone();
+        let counter: Callback<i32, i32> = Callback::new(move |_, _| {
+            *calls_copy.borrow_mut() += 1;
+        });
+
+        let _dispatcher = store.subscribe_event(&dispatcher, 1);
+        let _panicker = store.subscribe(&panicker);
+        let _counter = store.subscribe(&counter);
+
+        store.dispatch(1);
+        assert_eq!(11, *store.state());
+        assert_eq!(
+            vec![
+                (PanicSource::Listener, "listener".to_string()),
+                (PanicSource::Dispatch, "zero action".to_string()),
+            ],
+            *panics.borrow()
+        );
+        assert_eq!(2, *calls.borrow());
+
+        store.dispatch(5);
+        assert_eq!(16, *store.state());
+        assert_eq!(2, panics.borrow().len());
+    }
+
+    /// Panics whenever it is told about an error.
+    struct PanicOnErrorMiddleware;
+
+    impl Middleware<i32, i32, (), ()> for PanicOnErrorMiddleware {
+        fn on_error(&self, _store: &Store<i32, i32, (), ()>, _error: &ReduceError) {
+            panic!("on_error");
+        }
+    }
+
+    #[test]
+    fn test_panic_handler_on_error() {
+        let reducer: TryReducerFn<i32, i32, (), (), &str> = TryReducerFn(|state, action| {
+            if **state + action < 0 {
+                return Err("negative");
+            }
+            Ok(ReducerResult {
+                state: Rc::new(**state + action),
+                events: vec![()],
+                effects: vec![],
+            })
+        });
+        let store = Store::new_fallible(reducer, 1);
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        store.add_middleware(PanicOnErrorMiddleware);
+        store.add_middleware(TestErrorMiddleware {
+            errors: errors.clone(),
+        });
+        let listener_errors = Rc::new(RefCell::new(0));
+        let listener_errors_copy = listener_errors.clone();
+        let _errors = store.subscribe_errors(move |_| {
+            *listener_errors_copy.borrow_mut() += 1;
+        });
+
+        let panics = Rc::new(RefCell::new(Vec::new()));
+        let panics_copy = panics.clone();
+        store.set_panic_handler(move |panic| {
+            panics_copy
+                .borrow_mut()
+                .push((panic.source, panic.message.clone().unwrap()));
+        });
+
+        let report = store.dispatch(-2);
+        assert!(report.error.is_some());
+        assert_eq!(1, *store.state());
+        assert_eq!(
+            vec![(PanicSource::Middleware, "on_error".to_string())],
+            *panics.borrow()
+        );
+        assert_eq!(1, errors.borrow().len());
+        assert_eq!(1, *listener_errors.borrow());
+
+        store.dispatch(1);
+        assert_eq!(2, *store.state());
+    }
+
+    /// Records each hook invoked on it, and dispatches an action from
+    /// the hook named by `dispatch_from` when it sees the action `1`.
+    struct NestedDispatchMiddleware {
+        name: &'static str,
+        dispatch_from: &'static str,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl NestedDispatchMiddleware {
+        fn record(&self, store: &Store<i32, i32, i32, i32>, hook: &str, value: i32) {
+            self.log
+                .borrow_mut()
+                .push(format!("{} {} {}", self.name, hook, value));
+            if self.dispatch_from == hook && value == 1 {
+                store.dispatch(100);
+            }
+        }
+    }
+
+    impl Middleware<i32, i32, i32, i32> for NestedDispatchMiddleware {
+        fn on_reduce(
+            &self,
+            store: &Store<i32, i32, i32, i32>,
+            action: Option<&i32>,
+            next: ReduceNext<'_, i32, i32, i32, i32>,
+        ) -> ReduceMiddlewareResult<i32, i32> {
+            self.record(store, "reduce", *action.unwrap());
+            let result = next.reduce(action);
+            self.record(store, "reduced", *store.state());
+            result
+        }
+
+        fn process_effect(&self, store: &Store<i32, i32, i32, i32>, effect: i32) -> Option<i32> {
+            self.record(store, "effect", effect);
+            Some(effect)
+        }
+
+        fn on_notify(
+            &self,
+            store: &Store<i32, i32, i32, i32>,
+            events: Vec<i32>,
+            next: NotifyNext<'_, i32, i32, i32, i32>,
+        ) -> Vec<i32> {
+            self.record(store, "notify", events[0]);
+            next.notify(events)
+        }
+    }
+
+    /// Dispatch `1` through two [NestedDispatchMiddleware], named `a`
+    /// and `b`, which dispatch `100` from the given hooks, returning
+    /// the final state, the middleware log and the notified events.
+    fn nested_dispatch(a_from: &'static str, b_from: &'static str) -> (i32, Vec<String>, Vec<i32>) {
+        let reducer: ReducerFn<i32, i32, i32, i32> = |state, action| ReducerResult {
+            state: Rc::new(**state + action),
+            events: vec![*action],
+            effects: vec![*action],
+        };
+        let store = Store::new(reducer, 0);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        store.add_middleware(NestedDispatchMiddleware {
+            name: "a",
+            dispatch_from: a_from,
+            log: log.clone(),
+        });
+        store.add_middleware(NestedDispatchMiddleware {
+            name: "b",
+            dispatch_from: b_from,
+            log: log.clone(),
+        });
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events_copy = events.clone();
+        let callback: Callback<i32, i32> = Callback::new(move |_, event| {
+            events_copy.borrow_mut().push(event.unwrap());
+        });
+        let _subscription = store.subscribe_filter(&callback, |_: &i32| true);
+
+        store.dispatch(1);
+
+        let state = *store.state();
+        let log = log.borrow().clone();
+        let events = events.borrow().clone();
+        (state, log, events)
+    }
+
+    /// The log of each `(action, state)` going through the whole chain
+    /// of a [nested_dispatch()], one after the other.
+    fn nested_dispatch_log(actions: &[(i32, i32)]) -> Vec<String> {
+        actions
+            .iter()
+            .flat_map(|(action, state)| {
+                vec![
+                    format!("a reduce {}", action),
+                    format!("b reduce {}", action),
+                    format!("b reduced {}", state),
+                    format!("a reduced {}", state),
+                    format!("a effect {}", action),
+                    format!("b effect {}", action),
+                    format!("a notify {}", action),
+                    format!("b notify {}", action),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_dispatch_from_middleware() {
+        for (a_from, b_from) in [("reduce", ""), ("", "reduce"), ("reduced", "")] {
+            let (state, log, events) = nested_dispatch(a_from, b_from);
+            assert_eq!(101, state, "dispatch from {:?}", (a_from, b_from));
+            assert_eq!(
+                nested_dispatch_log(&[(1, 1), (100, 101)]),
+                log,
+                "dispatch from {:?}",
+                (a_from, b_from)
+            );
+            assert_eq!(vec![1, 100], events);
+        }
+    }
+
+    #[test]
+    fn test_nested_dispatch_from_process_effect() {
+        for (a_from, b_from) in [("effect", ""), ("", "effect")] {
+            let (state, log, events) = nested_dispatch(a_from, b_from);
+            assert_eq!(101, state, "dispatch from {:?}", (a_from, b_from));
+            assert_eq!(
+                nested_dispatch_log(&[(1, 1), (100, 101)]),
+                log,
+                "dispatch from {:?}",
+                (a_from, b_from)
+            );
+            assert_eq!(vec![1, 100], events);
+        }
+    }
+
+    #[test]
+    fn test_nested_dispatch_from_on_notify() {
+        for (a_from, b_from) in [("notify", ""), ("", "notify")] {
+            let (state, log, events) = nested_dispatch(a_from, b_from);
+            assert_eq!(101, state, "dispatch from {:?}", (a_from, b_from));
+            assert_eq!(
+                nested_dispatch_log(&[(1, 1), (100, 101)]),
+                log,
+                "dispatch from {:?}",
+                (a_from, b_from)
+            );
+            assert_eq!(vec![1, 100], events);
+        }
+    }
+
+    #[test]
+    fn test_nested_dispatch_interleaved() {
+        // Both middleware dispatch while the chain for `1` is part of
+        // the way through, from different hooks, so the chain must
+        // finish before either nested action starts its own.
+        for (a_from, b_from) in [
+            ("notify", "reduce"),
+            ("reduce", "notify"),
+            ("effect", "reduced"),
+        ] {
+            let (state, log, events) = nested_dispatch(a_from, b_from);
+            assert_eq!(201, state, "dispatch from {:?}", (a_from, b_from));
+            assert_eq!(
+                nested_dispatch_log(&[(1, 1), (100, 101), (100, 201)]),
+                log,
+                "dispatch from {:?}",
+                (a_from, b_from)
+            );
+            assert_eq!(vec![1, 100, 100], events);
+        }
+    }
+
+    /// Replaces each action with one twice as large.
+    struct DoubleMiddleware;
+
+    impl Middleware<i32, i32, i32, i32> for DoubleMiddleware {
+        fn on_reduce(
+            &self,
+            _store: &Store<i32, i32, i32, i32>,
+            action: Option<&i32>,
+            next: ReduceNext<'_, i32, i32, i32, i32>,
+        ) -> ReduceMiddlewareResult<i32, i32> {
+            next.reduce(action.map(|action| action * 2))
+        }
+    }
+
+    /// Splits each action `n` into `n` actions of `1`.
+    struct SplitMiddleware;
+
+    impl Middleware<i32, i32, i32, i32> for SplitMiddleware {
+        fn on_reduce(
+            &self,
+            _store: &Store<i32, i32, i32, i32>,
+            action: Option<&i32>,
+            next: ReduceNext<'_, i32, i32, i32, i32>,
+        ) -> ReduceMiddlewareResult<i32, i32> {
+            match action {
+                Some(action) => next.reduce_all((0..*action).map(|_| 1)),
+                None => next.reduce(action),
+            }
+        }
+    }
+
+    /// Drops every action.
+    struct DropMiddleware;
+
+    impl Middleware<i32, i32, i32, i32> for DropMiddleware {
+        fn on_reduce(
+            &self,
+            _store: &Store<i32, i32, i32, i32>,
+            _action: Option<&i32>,
+            next: ReduceNext<'_, i32, i32, i32, i32>,
+        ) -> ReduceMiddlewareResult<i32, i32> {
+            next.reduce_none()
+        }
+    }
+
+    #[test]
+    fn test_drop_action_in_middleware() {
+        let reducer: ReducerFn<i32, i32, i32, i32> = |state, action| ReducerResult {
+            state: Rc::new(**state + action),
+            events: vec![*action],
+            effects: vec![],
+        };
+        let store = Store::new(reducer, 0);
+        store.add_middleware(DropMiddleware);
+        store.add_middleware(DoubleMiddleware);
+
+        let report = store.dispatch(2);
+        assert_eq!(0, *store.state());
+        assert!(report.events.is_empty());
+    }
+
+    #[test]
+    fn test_replace_and_split_actions_in_middleware() {
+        let reducer: ReducerFn<i32, i32, i32, i32> = |state, action| ReducerResult {
+            state: Rc::new(**state + action),
+            events: vec![*action],
+            effects: vec![],
+        };
+        let store = Store::new(reducer, 0);
+        store.add_middleware(DoubleMiddleware);
+        store.add_middleware(SplitMiddleware);
+
+        let report = store.dispatch(2);
+        assert_eq!(4, *store.state());
+        assert_eq!(vec![1, 1, 1, 1], report.events);
+
+        // Each of the split actions resumes the chain from the split
+        // middleware's own position, so every one of them is doubled.
+        let store = Store::new(reducer, 0);
+        store.add_middleware(SplitMiddleware);
+        store.add_middleware(DoubleMiddleware);
+
+        let report = store.dispatch(3);
+        assert_eq!(6, *store.state());
+        assert_eq!(vec![2, 2, 2], report.events);
+    }
 }
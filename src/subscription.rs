@@ -0,0 +1,371 @@
+use crate::{AsListener, BatchListener, Callback, Listener, ReduceError};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    fmt::Debug,
+    hash::Hash,
+    rc::{Rc, Weak},
+};
+
+/// A unique identifier for a [Subscription] to a
+/// [Store](crate::Store).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SubscriptionId(u64);
+
+/// When a listener subscribed to all changes in a
+/// [Store](crate::Store) (rather than to specific events) is
+/// notified, see
+/// [Store::set_notify_policy()](crate::Store::set_notify_policy())
+/// and
+/// [Store::subscribe_with_policy()](crate::Store::subscribe_with_policy()).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum NotifyPolicy {
+    /// Notify the listener only when the dispatch produced events.
+    #[default]
+    OnEvents,
+    /// Notify the listener only when the dispatch replaced the state,
+    /// whether or not it produced events. The state is considered
+    /// changed if the [Reducer](crate::Reducer) returned a different
+    /// `Rc` (compared with [Rc::ptr_eq()]).
+    OnStateChange,
+    /// Notify the listener after every dispatch.
+    Always,
+}
+
+impl NotifyPolicy {
+    /// Whether a listener with this policy should be notified of a
+    /// dispatch.
+    pub(crate) fn should_notify(self, has_events: bool, state_changed: bool) -> bool {
+        match self {
+            NotifyPolicy::OnEvents => has_events,
+            NotifyPolicy::OnStateChange => state_changed,
+            NotifyPolicy::Always => true,
+        }
+    }
+}
+
+/// Something which a [Subscription] can be removed from.
+pub(crate) trait Unsubscribe {
+    /// Remove the subscription with the specified `id`, returns
+    /// `true` if it was present.
+    fn unsubscribe(&self, id: SubscriptionId) -> bool;
+}
+
+/// A handle to a [Listener] which has been subscribed to a
+/// [Store](crate::Store) using
+/// [Store::subscribe()](crate::Store::subscribe()) (or one of its
+/// variants).
+///
+/// When this handle is dropped (or
+/// [unsubscribe()](Subscription::unsubscribe()) is called), the
+/// listener is immediately removed from the store. If you would
+/// rather the listener remain subscribed for as long as its callback
+/// is alive, use [detach()](Subscription::detach()).
+#[must_use = "the listener is unsubscribed as soon as the Subscription is dropped"]
+pub struct Subscription {
+    id: SubscriptionId,
+    registry: Weak<dyn Unsubscribe>,
+}
+
+impl Subscription {
+    pub(crate) fn new(id: SubscriptionId, registry: Weak<dyn Unsubscribe>) -> Self {
+        Self { id, registry }
+    }
+
+    /// The unique id of this subscription, which can also be used
+    /// with [Store::unsubscribe()](crate::Store::unsubscribe()).
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    /// Remove the listener from the store immediately.
+    pub fn unsubscribe(self) {
+        drop(self)
+    }
+
+    /// Consume this handle without removing the listener from the
+    /// store. The listener will remain subscribed until its callback
+    /// is dropped, or it is removed using
+    /// [Store::unsubscribe()](crate::Store::unsubscribe()) with the
+    /// returned id.
+    pub fn detach(self) -> SubscriptionId {
+        let id = self.id;
+        std::mem::forget(self);
+        id
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.unsubscribe(self.id);
+        }
+    }
+}
+
+impl Debug for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Subscription({:?})", self.id.0)
+    }
+}
+
+/// Which `Event`s produced by a
+/// [Store::dispatch()](crate::Store::dispatch()) a [Listener] is
+/// listening to.
+pub(crate) enum EventFilter<Event> {
+    /// Listening to all changes, rather than to specific events.
+    All,
+    /// Listening to events for which the predicate returns `true`.
+    Predicate(Box<dyn Fn(&Event) -> bool>),
+}
+
+impl<Event: Hash + Eq + 'static> EventFilter<Event> {
+    /// Listen to events which are equal to any of the `events`, or to
+    /// all changes if there are none.
+    pub fn events(events: HashSet<Event>) -> Self {
+        if events.is_empty() {
+            EventFilter::All
+        } else {
+            EventFilter::Predicate(Box::new(move |event| events.contains(event)))
+        }
+    }
+}
+
+impl<Event> EventFilter<Event> {
+    /// Whether the listener should be notified of the `event`.
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            EventFilter::All => true,
+            EventFilter::Predicate(predicate) => predicate(event),
+        }
+    }
+}
+
+/// The kind of callback a listener was subscribed with.
+pub(crate) enum ListenerKind<State, Event> {
+    /// Invoked once for each matching event.
+    Single(Listener<State, Event>),
+    /// Invoked once per dispatch with all the matching events.
+    Batch(BatchListener<State, Event>),
+}
+
+impl<State, Event> ListenerKind<State, Event> {
+    /// Whether the callback is still alive.
+    fn is_alive(&self) -> bool {
+        match self {
+            ListenerKind::Single(listener) => listener.as_callback().is_some(),
+            ListenerKind::Batch(listener) => listener.as_callback().is_some(),
+        }
+    }
+}
+
+/// A [Listener] associated with (listening to) the `Events`s
+/// produced by a [Store::dispatch()](crate::Store::dispatch()) which
+/// match its [EventFilter].
+pub(crate) struct ListenerEntry<State, Event> {
+    pub id: SubscriptionId,
+    pub listener: ListenerKind<State, Event>,
+    pub filter: EventFilter<Event>,
+    /// The [NotifyPolicy] for this listener if it is subscribed to
+    /// all changes, otherwise the store's policy is used.
+    pub policy: Option<NotifyPolicy>,
+    /// Listeners with a higher priority are notified first, see
+    /// [Store::subscribe_with_priority()](crate::Store::subscribe_with_priority()).
+    pub priority: Cell<i32>,
+    /// A strong reference to the callback for listeners whose
+    /// callback was created by the store itself (rather than being
+    /// owned by the subscriber), which keeps it alive for as long as
+    /// the listener is subscribed.
+    pub _callback: Option<Callback<State, Event>>,
+    /// Set to `false` when this entry is removed from the
+    /// [ListenerRegistry], so that it is skipped by a notification
+    /// which is already in progress.
+    pub active: Cell<bool>,
+}
+
+impl<State, Event> Debug for ListenerEntry<State, Event> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ListenerEntry({:?})", self.id.0)
+    }
+}
+
+/// The listeners which have been subscribed to a
+/// [Store](crate::Store), kept in the order that they are notified:
+/// by descending priority, and then by the order in which they were
+/// subscribed.
+pub(crate) struct ListenerRegistry<State, Event> {
+    entries: RefCell<Vec<Rc<ListenerEntry<State, Event>>>>,
+    next_id: Cell<u64>,
+}
+
+impl<State, Event> ListenerRegistry<State, Event> {
+    pub fn new() -> Self {
+        Self {
+            entries: RefCell::new(Vec::new()),
+            next_id: Cell::new(0),
+        }
+    }
+
+    /// Add a new listener, returning its id.
+    pub fn add(
+        &self,
+        listener: ListenerKind<State, Event>,
+        filter: EventFilter<Event>,
+        policy: Option<NotifyPolicy>,
+        priority: i32,
+    ) -> SubscriptionId {
+        self.add_entry(listener, filter, policy, priority, None)
+    }
+
+    /// Add a new listener which holds the only strong reference to
+    /// its `callback`, returning its id.
+    pub fn add_callback(
+        &self,
+        callback: Callback<State, Event>,
+        filter: EventFilter<Event>,
+        policy: Option<NotifyPolicy>,
+    ) -> SubscriptionId {
+        let listener = ListenerKind::Single((&callback).as_listener());
+        self.add_entry(listener, filter, policy, 0, Some(callback))
+    }
+
+    /// Allocate a new id for a listener. This is also used for the
+    /// [ErrorListenerRegistry], so that [SubscriptionId]s are unique
+    /// for each store.
+    pub fn next_id(&self) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id.get());
+        self.next_id.set(id.0 + 1);
+        id
+    }
+
+    fn add_entry(
+        &self,
+        listener: ListenerKind<State, Event>,
+        filter: EventFilter<Event>,
+        policy: Option<NotifyPolicy>,
+        priority: i32,
+        callback: Option<Callback<State, Event>>,
+    ) -> SubscriptionId {
+        let id = self.next_id();
+
+        self.insert(Rc::new(ListenerEntry {
+            id,
+            listener,
+            filter,
+            policy,
+            priority: Cell::new(priority),
+            _callback: callback,
+            active: Cell::new(true),
+        }));
+
+        id
+    }
+
+    /// Insert an entry after all the entries with the same or a
+    /// higher priority.
+    fn insert(&self, entry: Rc<ListenerEntry<State, Event>>) {
+        let mut entries = self.entries.borrow_mut();
+        let priority = entry.priority.get();
+        let index = entries
+            .iter()
+            .position(|other| other.priority.get() < priority)
+            .unwrap_or(entries.len());
+        entries.insert(index, entry);
+    }
+
+    /// Change the priority of the listener with the specified `id`,
+    /// moving it after all the listeners with the same or a higher
+    /// priority. Returns `true` if it was present.
+    pub fn set_priority(&self, id: SubscriptionId, priority: i32) -> bool {
+        let entry = {
+            let mut entries = self.entries.borrow_mut();
+            match entries.iter().position(|entry| entry.id == id) {
+                Some(index) => entries.remove(index),
+                None => return false,
+            }
+        };
+
+        entry.priority.set(priority);
+        self.insert(entry);
+        true
+    }
+
+    /// A snapshot of the listeners currently subscribed, in the order
+    /// that they should be notified. The registry is free to be
+    /// modified while the snapshot is in use.
+    pub fn snapshot(&self) -> Vec<Rc<ListenerEntry<State, Event>>> {
+        self.entries.borrow().clone()
+    }
+
+    /// The number of listeners currently subscribed.
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Remove all listeners whose callback has been dropped.
+    pub fn remove_dead(&self) {
+        self.entries.borrow_mut().retain(|entry| {
+            let alive = entry.listener.is_alive();
+            entry.active.set(alive);
+            alive
+        });
+    }
+}
+
+impl<State, Event> Unsubscribe for ListenerRegistry<State, Event> {
+    fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut entries = self.entries.borrow_mut();
+        match entries.iter().position(|entry| entry.id == id) {
+            Some(index) => {
+                entries.remove(index).active.set(false);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A callback which is notified of a [ReduceError].
+pub(crate) type ErrorCallback = Rc<dyn Fn(&ReduceError)>;
+
+/// The error listeners which have been subscribed to a
+/// [Store](crate::Store) using
+/// [Store::subscribe_errors()](crate::Store::subscribe_errors()).
+pub(crate) struct ErrorListenerRegistry {
+    entries: RefCell<Vec<(SubscriptionId, ErrorCallback)>>,
+}
+
+impl ErrorListenerRegistry {
+    pub fn new() -> Self {
+        Self {
+            entries: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn add(&self, id: SubscriptionId, callback: ErrorCallback) {
+        self.entries.borrow_mut().push((id, callback));
+    }
+
+    /// A snapshot of the callbacks currently subscribed. The registry
+    /// is free to be modified while the snapshot is in use.
+    pub fn snapshot(&self) -> Vec<ErrorCallback> {
+        self.entries
+            .borrow()
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect()
+    }
+}
+
+impl Unsubscribe for ErrorListenerRegistry {
+    fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut entries = self.entries.borrow_mut();
+        match entries.iter().position(|(entry_id, _)| *entry_id == id) {
+            Some(index) => {
+                entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
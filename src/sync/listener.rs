@@ -0,0 +1,67 @@
+use std::sync::{Arc, Weak};
+
+/// A trait to take a [Callback] or other custom callback type and
+/// produce a [Listener], a weak reference to that callback.
+pub trait AsListener<State, Event> {
+    /// Produce a [Listener], a weak reference to this callback.
+    fn as_listener(&self) -> Listener<State, Event>;
+}
+
+/// A weak reference to a callback function (usually [Callback]) which
+/// is notified of changes to [Store](super::Store) `State`, and
+/// `Event`s produced by the store.
+///
+/// This is the thread-safe equivalent of
+/// [Listener](crate::Listener).
+#[allow(clippy::type_complexity)]
+#[derive(Clone)]
+pub struct Listener<State, Event>(Weak<dyn Fn(Arc<State>, Option<Event>) + Send + Sync>);
+
+impl<State, Event> Listener<State, Event> {
+    /// Attempt to upgrade the weak reference in this listener to a
+    /// [Callback], otherwise if unable to, returns `None`.
+    pub fn as_callback(&self) -> Option<Callback<State, Event>> {
+        self.0.upgrade().map(Callback)
+    }
+}
+
+impl<State, Event> AsListener<State, Event> for Listener<State, Event> {
+    fn as_listener(&self) -> Listener<State, Event> {
+        Listener(self.0.clone())
+    }
+}
+
+/// A wrapper for a callback which is notified of changes to
+/// [Store](super::Store) `State`, and `Event`s produced by the store.
+///
+/// This is the thread-safe equivalent of
+/// [Callback](crate::Callback). The callback may be invoked on
+/// whichever thread happens to be processing the store's dispatch
+/// queue, so it is required to be `Send + Sync`.
+#[allow(clippy::type_complexity)]
+#[derive(Clone)]
+pub struct Callback<State, Event>(Arc<dyn Fn(Arc<State>, Option<Event>) + Send + Sync>);
+
+impl<State, Event> AsListener<State, Event> for &Callback<State, Event> {
+    fn as_listener(&self) -> Listener<State, Event> {
+        Listener(Arc::downgrade(&self.0))
+    }
+}
+
+impl<State, Event> Callback<State, Event> {
+    pub fn new<C: Fn(Arc<State>, Option<Event>) + Send + Sync + 'static>(closure: C) -> Self {
+        Callback(Arc::new(closure))
+    }
+    pub fn emit(&self, state: Arc<State>, event: Option<Event>) {
+        (self.0)(state, event)
+    }
+}
+
+impl<C, State, Event> From<C> for Callback<State, Event>
+where
+    C: Fn(Arc<State>, Option<Event>) + Send + Sync + 'static,
+{
+    fn from(closure: C) -> Self {
+        Callback(Arc::new(closure))
+    }
+}
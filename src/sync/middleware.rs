@@ -0,0 +1,146 @@
+//! [Middleware] used to modify the behaviour of a thread-safe
+//! [Store] during a [Store::dispatch()].
+
+use super::Store;
+pub use crate::middleware::ReduceMiddlewareResult;
+use std::borrow::Borrow;
+
+/// Executes subsequent middleware and then runs the
+/// [Reducer](super::Reducer), see [Middleware::on_reduce()].
+///
+/// This is the thread-safe equivalent of
+/// [ReduceNext](crate::middleware::ReduceNext). Each invocation of
+/// [Middleware::on_reduce()] is given its own `ReduceNext`, which
+/// records the position in the middleware chain, so the chain is
+/// unaffected by any other chains which are run before it is resumed.
+pub struct ReduceNext<'a, State, Action, Event, Effect> {
+    store: &'a Store<State, Action, Event, Effect>,
+    index: usize,
+}
+
+impl<'a, State, Action, Event, Effect> ReduceNext<'a, State, Action, Event, Effect> {
+    pub(crate) fn new(store: &'a Store<State, Action, Event, Effect>, index: usize) -> Self {
+        Self { store, index }
+    }
+
+    /// Execute the subsequent middleware with the `action`, and then
+    /// run the [Reducer](super::Reducer). The `action` may be
+    /// borrowed or owned. If `action` is `None`, the reducer is not
+    /// run (see [ReduceNext::reduce_none()]).
+    pub fn reduce<A: Borrow<Action>>(
+        &self,
+        action: Option<A>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        self.store
+            .middleware_reduce_next(self.index, action.as_ref().map(Borrow::borrow))
+    }
+
+    /// Execute the subsequent middleware without an action, so the
+    /// [Reducer](super::Reducer) is not run. This is equivalent to
+    /// `reduce(None)`, without needing to name the action type.
+    pub fn reduce_none(&self) -> ReduceMiddlewareResult<Event, Effect> {
+        self.store.middleware_reduce_next(self.index, None)
+    }
+
+    /// Execute the subsequent middleware and then run the
+    /// [Reducer](super::Reducer) with each of the `actions` in turn,
+    /// returning the events and effects produced by all of them.
+    pub fn reduce_all<A, I>(&self, actions: I) -> ReduceMiddlewareResult<Event, Effect>
+    where
+        A: Borrow<Action>,
+        I: IntoIterator<Item = A>,
+    {
+        let mut result = ReduceMiddlewareResult::default();
+        for action in actions {
+            let action_result = self.reduce(Some(action));
+            result.events.extend(action_result.events);
+            result.effects.extend(action_result.effects);
+        }
+        result
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Clone for ReduceNext<'a, State, Action, Event, Effect> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Copy for ReduceNext<'a, State, Action, Event, Effect> {}
+
+/// Executes subsequent middleware and then notifies the listeners,
+/// see [Middleware::on_notify()].
+///
+/// This is the thread-safe equivalent of
+/// [NotifyNext](crate::middleware::NotifyNext).
+pub struct NotifyNext<'a, State, Action, Event, Effect> {
+    store: &'a Store<State, Action, Event, Effect>,
+    index: usize,
+}
+
+impl<'a, State, Action, Event, Effect> NotifyNext<'a, State, Action, Event, Effect> {
+    pub(crate) fn new(store: &'a Store<State, Action, Event, Effect>, index: usize) -> Self {
+        Self { store, index }
+    }
+
+    /// Execute the subsequent middleware with the `events`, returning
+    /// the events which the listeners will be notified of.
+    pub fn notify(&self, events: Vec<Event>) -> Vec<Event> {
+        self.store.middleware_notify_next(self.index, events)
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Clone for NotifyNext<'a, State, Action, Event, Effect> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, State, Action, Event, Effect> Copy for NotifyNext<'a, State, Action, Event, Effect> {}
+
+/// `Middleware` used to modify the behaviour of a thread-safe
+/// [Store] during a [Store::dispatch()].
+///
+/// This is the thread-safe equivalent of
+/// [Middleware](crate::middleware::Middleware), see its documentation
+/// for details about each of the hooks. Middleware is only ever
+/// invoked by the thread which is currently processing the store's
+/// dispatch queue, one action at a time.
+pub trait Middleware<State, Action, Event, Effect>: Send + Sync {
+    /// Invoked just before the `Action` is sent to the
+    /// [Reducer](super::Reducer). It is necessary to call
+    /// [ReduceNext::reduce()] on the provided `next`, which executes
+    /// subsequent middleware and runs the [Reducer](super::Reducer).
+    fn on_reduce(
+        &self,
+        _store: &Store<State, Action, Event, Effect>,
+        action: Option<&Action>,
+        next: ReduceNext<'_, State, Action, Event, Effect>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        next.reduce(action)
+    }
+
+    /// Process an `Effect`. Returns `None` if the effect was
+    /// processed/consumed by this handler, otherwise returns
+    /// `Some(effect)`.
+    fn process_effect(
+        &self,
+        _store: &Store<State, Action, Event, Effect>,
+        effect: Effect,
+    ) -> Option<Effect> {
+        Some(effect)
+    }
+
+    /// Invoked just before resulting events are sent to the store
+    /// listeners. It is necessary to call [NotifyNext::notify()] on
+    /// the provided `next`, which executes subsequent middleware and
+    /// then notifies the listeners.
+    fn on_notify(
+        &self,
+        _store: &Store<State, Action, Event, Effect>,
+        events: Vec<Event>,
+        next: NotifyNext<'_, State, Action, Event, Effect>,
+    ) -> Vec<Event> {
+        next.notify(events)
+    }
+}
@@ -0,0 +1,59 @@
+//! A thread-safe variant of the [Store](crate::Store) and its
+//! associated types, backed by [Arc](std::sync::Arc),
+//! [Mutex](std::sync::Mutex) and [RwLock](std::sync::RwLock) instead
+//! of [Rc](std::rc::Rc), [RefCell](std::cell::RefCell) and
+//! [Cell](std::cell::Cell).
+//!
+//! The types in this module mirror the ones in the crate root (the
+//! same [Reducer], [Middleware](middleware::Middleware), [Listener]
+//! and [Callback] model), but the [Store] is `Send + Sync`, so it can
+//! be shared with worker threads (usually via a [StoreRef]) and
+//! actions can be dispatched from any of them.
+//!
+//! ## Example
+//!
+//! ```
+//! use reactive_state::sync::{Callback, ReducerFn, ReducerResult, StoreRef};
+//! use std::sync::{
+//!     atomic::{AtomicU32, Ordering},
+//!     Arc,
+//! };
+//!
+//! let reducer: ReducerFn<u32, u32, (), ()> = |state, action| ReducerResult {
+//!     state: Arc::new(**state + *action),
+//!     events: vec![()],
+//!     effects: vec![],
+//! };
+//!
+//! let store = StoreRef::new(reducer, 0);
+//!
+//! let invokes = Arc::new(AtomicU32::new(0));
+//! let invokes_local = invokes.clone();
+//! let callback = Callback::new(move |_state, _event| {
+//!     invokes_local.fetch_add(1, Ordering::SeqCst);
+//! });
+//! store.subscribe(&callback);
+//!
+//! let threads: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         let store = store.clone();
+//!         std::thread::spawn(move || store.dispatch(1u32))
+//!     })
+//!     .collect();
+//!
+//! for thread in threads {
+//!     thread.join().unwrap();
+//! }
+//!
+//! assert_eq!(4, *store.state());
+//! assert_eq!(4, invokes.load(Ordering::SeqCst));
+//! ```
+
+mod listener;
+pub mod middleware;
+mod reducer;
+mod store;
+
+pub use listener::*;
+pub use reducer::*;
+pub use store::{Store, StoreRef};
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+/// A wrapper for a function that implements the [Reducer](Reducer)
+/// trait.
+///
+/// This is the thread-safe equivalent of
+/// [ReducerFn](crate::ReducerFn), see its documentation for an
+/// example of how a reducer function should work.
+pub type ReducerFn<State, Action, Event, Effect> =
+    fn(&Arc<State>, &Action) -> ReducerResult<State, Event, Effect>;
+
+impl<T, State, Action, Event, Effect> Reducer<State, Action, Event, Effect> for T
+where
+    T: Fn(&Arc<State>, &Action) -> ReducerResult<State, Event, Effect>,
+{
+    fn reduce(
+        &self,
+        prev_state: &Arc<State>,
+        action: &Action,
+    ) -> ReducerResult<State, Event, Effect> {
+        (self)(prev_state, action)
+    }
+}
+
+/// The thread-safe equivalent of [Reducer](crate::Reducer), used by
+/// a [Store](super::Store).
+///
+/// Using the [reduce()](Reducer::reduce()) method, implementors of
+/// this trait take an `Action` submitted to a store via
+/// [Store::dispatch()](super::Store::dispatch()) and modifies the
+/// `State` in the store, producing a new `State`, and also producing
+/// events and effects associated with the `Action` and state
+/// modifications that occurred.
+///
+/// See [Reducer::reduce()](crate::Reducer::reduce()) for more details
+/// about how this method is expected to behave.
+pub trait Reducer<State, Action, Event, Effect> {
+    /// Take an `Action` submitted to a store via
+    /// [Store::dispatch()](super::Store::dispatch()) and modifies the
+    /// `prev_state`, producing a new `State`, and also producing
+    /// events associated with the `Action` and state modifications
+    /// that occurred.
+    fn reduce(
+        &self,
+        prev_state: &Arc<State>,
+        action: &Action,
+    ) -> ReducerResult<State, Event, Effect>;
+}
+
+/// The result of a [Reducer::reduce()] function.
+///
+/// This is the thread-safe equivalent of
+/// [ReducerResult](crate::ReducerResult).
+pub struct ReducerResult<State, Event, Effect> {
+    pub state: Arc<State>,
+    pub events: Vec<Event>,
+    pub effects: Vec<Effect>,
+}
+
+impl<State, Event, Effect> Default for ReducerResult<State, Event, Effect>
+where
+    State: Default,
+{
+    fn default() -> Self {
+        Self {
+            state: Arc::new(State::default()),
+            events: vec![],
+            effects: vec![],
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use super::{
+    middleware::{Middleware, NotifyNext, ReduceMiddlewareResult, ReduceNext},
+    AsListener, Listener, Reducer,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+    ops::Deref,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, TryLockError},
+};
+
+/// Lock the `mutex`, ignoring poisoning. Apart from the store's
+/// `dispatch_lock`, none of the locks in the [Store] are held while
+/// user code (reducers, middleware or callbacks) is running, so the
+/// data they protect is always in a consistent state.
+///
+/// The `dispatch_lock` is held for as long as the dispatch queue is
+/// being processed, across the reducer, middleware and listener
+/// calls, so that only one thread at a time processes the queue. It
+/// is only ever taken with [Mutex::try_lock()] rather than with this
+/// function, so a dispatch made while it is held (re-entrantly from
+/// user code, or from another thread) queues its action for the
+/// holder to process instead of deadlocking. It protects no data, so
+/// its poisoning is ignored too.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A [Listener] associated with (listening to) a given set of
+/// `Events`s produced by a [Store::dispatch()].
+struct ListenerEventPair<State, Event> {
+    pub listener: Listener<State, Event>,
+    pub events: HashSet<Event>,
+}
+
+impl<State, Event> Debug for ListenerEventPair<State, Event> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ListenerEventPair")
+    }
+}
+
+/// An action to modify some aspect of the [Store], to be stored in a
+/// queue and executed at the start of a [Store::dispatch()] for a
+/// given `Action`.
+enum StoreModification<State, Action, Event, Effect> {
+    AddListener(Arc<ListenerEventPair<State, Event>>),
+    AddMiddleware(Arc<dyn Middleware<State, Action, Event, Effect>>),
+}
+
+/// A wrapper for an [Arc] reference to a [Store].
+///
+/// This wrapper exists to provide a standard interface for re-useable
+/// middleware and other components which may require a long living
+/// reference to the store in order to dispatch actions or modify it
+/// in some manner that could not be handled by a simple `&Store`. It
+/// can be cloned and sent to other threads.
+pub struct StoreRef<State, Action, Event, Effect>(Arc<Store<State, Action, Event, Effect>>);
+
+impl<State, Action, Event, Effect> StoreRef<State, Action, Event, Effect>
+where
+    Event: Clone + Hash + Eq,
+{
+    pub fn new<R: Reducer<State, Action, Event, Effect> + Send + Sync + 'static>(
+        reducer: R,
+        initial_state: State,
+    ) -> Self {
+        Self(Arc::new(Store::new(reducer, initial_state)))
+    }
+}
+
+impl<State, Action, Event, Effect> Clone for StoreRef<State, Action, Event, Effect> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<State, Action, Event, Effect> Deref for StoreRef<State, Action, Event, Effect> {
+    type Target = Store<State, Action, Event, Effect>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<State, Action, Event, Effect> PartialEq for StoreRef<State, Action, Event, Effect> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The thread-safe equivalent of [Store](crate::Store).
+///
+/// This store behaves in the same manner as [Store](crate::Store),
+/// but it is `Send + Sync` (provided that the `State`, `Action`,
+/// `Event` and `Effect` are), so it can be shared between threads
+/// (usually via a [StoreRef]).
+///
+/// `Action`s may be dispatched from any thread via
+/// [Store::dispatch()]. Only one thread at a time processes the
+/// dispatch queue. If another thread is already processing the queue
+/// when an action is dispatched, the action is added to the queue and
+/// [Store::dispatch()] returns immediately; the action will be
+/// processed (and listeners notified) by the thread which is already
+/// processing the queue, before it returns from its own
+/// [Store::dispatch()].
+pub struct Store<State, Action, Event, Effect> {
+    /// This lock is used to prevent dispatch recursion, and to ensure
+    /// that only one thread at a time processes the dispatch queue.
+    dispatch_lock: Mutex<()>,
+    /// Queue of actions to be dispatched by [Store::dispatch()].
+    dispatch_queue: Mutex<VecDeque<Action>>,
+    /// Queue of [StoreModification]s to be executed by
+    /// [Store::dispatch()] before the next `Action` is dispatched.
+    modification_queue: Mutex<VecDeque<StoreModification<State, Action, Event, Effect>>>,
+    /// The [Reducer] for this store, which takes `Actions`, modifies
+    /// the `State` stored in this store, and produces `Events` to be
+    /// sent to the store listeners.
+    reducer: Box<dyn Reducer<State, Action, Event, Effect> + Send + Sync>,
+    /// The current state of this store.
+    state: RwLock<Arc<State>>,
+    /// The listeners which are notified of changes to the state of
+    /// this store, and events produced by this store during a
+    /// [Store::dispatch()].
+    listeners: Mutex<Vec<Arc<ListenerEventPair<State, Event>>>>,
+    /// Middleware which modifies the functionality of this store.
+    #[allow(clippy::type_complexity)]
+    middleware: RwLock<Vec<Arc<dyn Middleware<State, Action, Event, Effect>>>>,
+}
+
+/// The parts of the [Store] used by the middleware chain, which
+/// [ReduceNext] and [NotifyNext] can call without the `Event` bounds.
+impl<State, Action, Event, Effect> Store<State, Action, Event, Effect> {
+    /// Get the current `State` stored in this store.
+    ///
+    /// Modifications to this state need to be performed by
+    /// dispatching an `Action` to the store using
+    /// [dispatch()](Store::dispatch()).
+    pub fn state(&self) -> Arc<State> {
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Dispatch an `Action` to the reducer on this `Store` without
+    /// invoking middleware.
+    fn dispatch_reducer(&self, action: &Action) -> ReduceMiddlewareResult<Event, Effect> {
+        let result = self.reducer.reduce(&self.state(), action);
+        *self.state.write().unwrap_or_else(PoisonError::into_inner) = result.state;
+
+        ReduceMiddlewareResult {
+            events: result.events,
+            effects: result.effects,
+        }
+    }
+
+    /// The middleware at position `index` in the chain, if there is
+    /// one.
+    #[allow(clippy::type_complexity)]
+    fn middleware_at(
+        &self,
+        index: usize,
+    ) -> Option<Arc<dyn Middleware<State, Action, Event, Effect>>> {
+        self.middleware
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(index)
+            .cloned()
+    }
+
+    /// A recursive function which executes the middleware at position
+    /// `index` in the chain, giving it a [ReduceNext] to invoke the
+    /// next middleware, until all middleware has been invoked, at
+    /// which point the `Action` is sent to the reducer.
+    pub(crate) fn middleware_reduce_next(
+        &self,
+        index: usize,
+        action: Option<&Action>,
+    ) -> ReduceMiddlewareResult<Event, Effect> {
+        match self.middleware_at(index) {
+            Some(middleware) => {
+                middleware.on_reduce(self, action, ReduceNext::new(self, index + 1))
+            }
+            None => match action {
+                Some(action) => self.dispatch_reducer(action),
+                None => ReduceMiddlewareResult::default(),
+            },
+        }
+    }
+
+    /// A recursive function which executes the middleware at position
+    /// `index` in the chain, giving it a [NotifyNext] to invoke the
+    /// next middleware, until all middleware has been invoked.
+    pub(crate) fn middleware_notify_next(&self, index: usize, events: Vec<Event>) -> Vec<Event> {
+        match self.middleware_at(index) {
+            Some(middleware) => {
+                middleware.on_notify(self, events, NotifyNext::new(self, index + 1))
+            }
+            None => events,
+        }
+    }
+}
+
+impl<State, Action, Event, Effect> Store<State, Action, Event, Effect>
+where
+    Event: Clone + Hash + Eq,
+{
+    /// Create a new [Store], which uses the specified `reducer` to
+    /// handle `Action`s which mutate the state and produce `Event`s,
+    /// and with the `initial_state`.
+    pub fn new<R: Reducer<State, Action, Event, Effect> + Send + Sync + 'static>(
+        reducer: R,
+        initial_state: State,
+    ) -> Self {
+        Self {
+            dispatch_lock: Mutex::new(()),
+            dispatch_queue: Mutex::new(VecDeque::new()),
+            modification_queue: Mutex::new(VecDeque::new()),
+            reducer: Box::new(reducer),
+            state: RwLock::new(Arc::new(initial_state)),
+            listeners: Mutex::new(Vec::new()),
+            middleware: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Dispatch an `Action` to the reducer on this `Store`, invoking
+    /// all middleware's [on_reduce()][Middleware::on_reduce()] first.
+    fn middleware_reduce(&self, action: &Action) -> ReduceMiddlewareResult<Event, Effect> {
+        self.middleware_reduce_next(0, Some(action))
+    }
+
+    /// Process all the `Effect`s returned by the [Reducer::reduce()]
+    /// by invoking the middleware on this store to perform the
+    /// processing using [Middleware::process_effect()].
+    fn middleware_process_effects(&self, effects: Vec<Effect>) {
+        for effect in effects {
+            self.middleware_process_effects_next(0, effect);
+        }
+    }
+
+    /// A recursive function which executes the middleware at position
+    /// `index` in the chain to process the specified `Effect` with
+    /// [Middleware::process_effect()], and invokes the next
+    /// middleware, until all middleware has been invoked.
+    fn middleware_process_effects_next(&self, index: usize, effect: Effect) {
+        if let Some(middleware) = self.middleware_at(index) {
+            if let Some(effect) = middleware.process_effect(self, effect) {
+                self.middleware_process_effects_next(index + 1, effect);
+            }
+        }
+    }
+
+    /// Notify store listeners of events produced during a reduce as a
+    /// result of an `Action` being dispatched. Invokes all
+    /// middleware's [on_notify()][Middleware::on_notify()] first.
+    fn middleware_notify(&self, events: Vec<Event>) -> Vec<Event> {
+        self.middleware_notify_next(0, events)
+    }
+
+    /// Notify store listeners of events produced during a result of
+    /// an `Action` being dispatched.
+    ///
+    /// The listeners lock is not held while the callbacks are being
+    /// invoked, so that callbacks are free to interact with the
+    /// store.
+    fn notify_listeners(&self, events: Vec<Event>) {
+        let listeners: Vec<Arc<ListenerEventPair<State, Event>>> =
+            lock(&self.listeners).iter().cloned().collect();
+        let mut remove_dead = false;
+
+        for pair in &listeners {
+            match pair.listener.as_callback() {
+                Some(callback) => {
+                    if pair.events.is_empty() {
+                        callback.emit(self.state(), None);
+                    } else {
+                        //  call the listener for every matching listener event
+                        for event in &events {
+                            if pair.events.contains(event) {
+                                callback.emit(self.state(), Some(event.clone()));
+                            }
+                        }
+                    }
+                }
+                None => remove_dead = true,
+            }
+        }
+
+        if remove_dead {
+            lock(&self.listeners).retain(|pair| pair.listener.as_callback().is_some());
+        }
+    }
+
+    fn process_pending_modifications(&self) {
+        loop {
+            let modification = lock(&self.modification_queue).pop_front();
+
+            match modification {
+                Some(StoreModification::AddListener(listener_pair)) => {
+                    lock(&self.listeners).push(listener_pair);
+                }
+                Some(StoreModification::AddMiddleware(middleware)) => {
+                    self.middleware
+                        .write()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(middleware);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Dispatch an `Action` to be passed to the [Reducer] in order to
+    /// modify the `State` in this store, and produce `Events` to be
+    /// sent to the store listeners.
+    ///
+    /// If the store's dispatch queue is currently being processed
+    /// (either by another thread, or further up the stack of the
+    /// current thread), the action is queued and this method returns
+    /// immediately.
+    pub fn dispatch<A: Into<Action>>(&self, action: A) {
+        self.dispatch_impl(action.into());
+    }
+
+    /// Concrete version of [Store::dispatch()], for code size
+    /// reduction purposes, to avoid generating multiple versions of
+    /// this complex function per action that implements
+    /// `Into<Action>`.
+    fn dispatch_impl(&self, action: Action) {
+        lock(&self.dispatch_queue).push_back(action);
+
+        loop {
+            // If the lock fails to acquire, then the dispatch is already in
+            // progress, either on another thread, or this one (when a listener
+            // callback also triggers another dispatch). The thread holding the
+            // lock will process the action that was just queued.
+            let dispatch_lock = match self.dispatch_lock.try_lock() {
+                Ok(dispatch_lock) => dispatch_lock,
+                Err(TryLockError::Poisoned(error)) => error.into_inner(),
+                Err(TryLockError::WouldBlock) => return,
+            };
+
+            loop {
+                // The queue lock must not be held while the action is processed.
+                let dispatch_action = lock(&self.dispatch_queue).pop_front();
+
+                match dispatch_action {
+                    Some(action) => {
+                        self.process_pending_modifications();
+
+                        let ReduceMiddlewareResult { events, effects } =
+                            if self.middleware_at(0).is_none() {
+                                self.dispatch_reducer(&action)
+                            } else {
+                                self.middleware_reduce(&action)
+                            };
+
+                        self.middleware_process_effects(effects);
+
+                        let middleware_events = self.middleware_notify(events);
+                        if !middleware_events.is_empty() {
+                            self.notify_listeners(middleware_events);
+                        }
+                    }
+                    None => {
+                        break;
+                    }
+                }
+            }
+
+            drop(dispatch_lock);
+
+            // Another thread may have queued an action after the queue was
+            // found to be empty, but before the lock was released, in which
+            // case it is up to this thread to process it.
+            if lock(&self.dispatch_queue).is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// dispatched via [dispatch()](Store::dispatch()).
+    ///
+    /// The listener is a weak reference; when the strong reference
+    /// associated with it (usually [Callback](super::Callback)) is
+    /// dropped, the listener will be removed from this store upon
+    /// [dispatch()](Store::dispatch()).
+    pub fn subscribe<L: AsListener<State, Event>>(&self, listener: L) {
+        self.subscribe_events(listener, Vec::new());
+    }
+
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// being dispatched via [dispatch()](Store::dispatch()) and
+    /// reduced with the store's [Reducer]. This subscription is only
+    /// active changes which produce the specific matching `event`
+    /// from the [Reducer].
+    pub fn subscribe_event<L: AsListener<State, Event>>(&self, listener: L, event: Event) {
+        self.subscribe_events(listener, vec![event]);
+    }
+
+    /// Subscribe a [Listener] to changes in the store state and
+    /// events produced by the [Reducer] as a result of `Action`s
+    /// being dispatched via [dispatch()](Store::dispatch()) and
+    /// reduced with the store's [Reducer]. This subscription is only
+    /// active changes which produce any of the specific matching
+    /// `events` from the [Reducer].
+    pub fn subscribe_events<L: AsListener<State, Event>, E: IntoIterator<Item = Event>>(
+        &self,
+        listener: L,
+        events: E,
+    ) {
+        lock(&self.modification_queue).push_back(StoreModification::AddListener(Arc::new(
+            ListenerEventPair {
+                listener: listener.as_listener(),
+                events: events.into_iter().collect(),
+            },
+        )));
+    }
+
+    /// Add [Middleware] to modify the behaviour of this [Store]
+    /// during a [dispatch()](Store::dispatch()).
+    pub fn add_middleware<M: Middleware<State, Action, Event, Effect> + 'static>(
+        &self,
+        middleware: M,
+    ) {
+        lock(&self.modification_queue)
+            .push_back(StoreModification::AddMiddleware(Arc::new(middleware)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sync::{
+        middleware::{Middleware, ReduceMiddlewareResult, ReduceNext},
+        Callback, Reducer, ReducerResult, Store, StoreRef,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct TestState {
+        counter: i32,
+    }
+
+    #[derive(Copy, Clone)]
+    enum TestAction {
+        Increment,
+        Decrement,
+        Decrent2Then1,
+    }
+
+    enum TestEffect {
+        ChainAction(TestAction),
+    }
+
+    #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+    enum TestEvent {
+        CounterIsZero,
+        CounterChanged,
+    }
+
+    struct TestReducer;
+
+    impl Reducer<TestState, TestAction, TestEvent, TestEffect> for TestReducer {
+        fn reduce(
+            &self,
+            state: &Arc<TestState>,
+            action: &TestAction,
+        ) -> ReducerResult<TestState, TestEvent, TestEffect> {
+            let mut events = vec![TestEvent::CounterChanged];
+            let mut effects = Vec::new();
+
+            let counter = match action {
+                TestAction::Increment => state.counter + 1,
+                TestAction::Decrement => state.counter - 1,
+                TestAction::Decrent2Then1 => {
+                    effects.push(TestEffect::ChainAction(TestAction::Decrement));
+                    state.counter - 2
+                }
+            };
+
+            if counter == 0 {
+                events.push(TestEvent::CounterIsZero);
+            }
+
+            ReducerResult {
+                state: Arc::new(TestState { counter }),
+                events,
+                effects,
+            }
+        }
+    }
+
+    struct TestReduceMiddleware {
+        new_action: TestAction,
+    }
+
+    impl Middleware<TestState, TestAction, TestEvent, TestEffect> for TestReduceMiddleware {
+        fn on_reduce(
+            &self,
+            _store: &Store<TestState, TestAction, TestEvent, TestEffect>,
+            action: Option<&TestAction>,
+            next: ReduceNext<'_, TestState, TestAction, TestEvent, TestEffect>,
+        ) -> ReduceMiddlewareResult<TestEvent, TestEffect> {
+            next.reduce(action.map(|_| self.new_action))
+        }
+    }
+
+    /// Reduces each action twice.
+    struct TestDuplicateMiddleware;
+
+    impl Middleware<TestState, TestAction, TestEvent, TestEffect> for TestDuplicateMiddleware {
+        fn on_reduce(
+            &self,
+            _store: &Store<TestState, TestAction, TestEvent, TestEffect>,
+            action: Option<&TestAction>,
+            next: ReduceNext<'_, TestState, TestAction, TestEvent, TestEffect>,
+        ) -> ReduceMiddlewareResult<TestEvent, TestEffect> {
+            next.reduce_all(action.into_iter().chain(action))
+        }
+    }
+
+    /// Counts the actions which reach it.
+    struct TestCountMiddleware {
+        count: Arc<AtomicUsize>,
+    }
+
+    impl Middleware<TestState, TestAction, TestEvent, TestEffect> for TestCountMiddleware {
+        fn on_reduce(
+            &self,
+            _store: &Store<TestState, TestAction, TestEvent, TestEffect>,
+            action: Option<&TestAction>,
+            next: ReduceNext<'_, TestState, TestAction, TestEvent, TestEffect>,
+        ) -> ReduceMiddlewareResult<TestEvent, TestEffect> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            next.reduce(action)
+        }
+    }
+
+    struct TestEffectMiddleware;
+
+    impl Middleware<TestState, TestAction, TestEvent, TestEffect> for TestEffectMiddleware {
+        fn process_effect(
+            &self,
+            store: &Store<TestState, TestAction, TestEvent, TestEffect>,
+            effect: TestEffect,
+        ) -> Option<TestEffect> {
+            match effect {
+                TestEffect::ChainAction(action) => store.dispatch(action),
+            }
+
+            None
+        }
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Store<TestState, TestAction, TestEvent, TestEffect>>();
+        assert_send_sync::<StoreRef<TestState, TestAction, TestEvent, TestEffect>>();
+    }
+
+    #[test]
+    fn test_notify() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+
+        let callback_test = Arc::new(Mutex::new(0));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> =
+            Callback::new(move |state: Arc<TestState>, _| {
+                *callback_test_copy.lock().unwrap() = state.counter;
+            });
+
+        store.subscribe(&callback);
+
+        store.dispatch(TestAction::Increment);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(2, *callback_test.lock().unwrap());
+        assert_eq!(2, store.state().counter);
+
+        store.dispatch(TestAction::Decrement);
+        assert_eq!(1, store.state().counter);
+    }
+
+    #[test]
+    fn test_subscribe_event() {
+        let store = StoreRef::new(TestReducer, TestState { counter: -2 });
+
+        let callback_test: Arc<Mutex<Vec<TestEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> = Callback::new(move |_, event| {
+            callback_test_copy.lock().unwrap().push(event.unwrap());
+        });
+
+        store.subscribe_event(&callback, TestEvent::CounterIsZero);
+        store.dispatch(TestAction::Increment);
+        assert!(callback_test.lock().unwrap().is_empty());
+        store.dispatch(TestAction::Increment);
+        assert_eq!(
+            vec![TestEvent::CounterIsZero],
+            *callback_test.lock().unwrap()
+        );
+
+        drop(callback);
+        store.dispatch(TestAction::Decrement);
+        store.dispatch(TestAction::Increment);
+        assert_eq!(1, callback_test.lock().unwrap().len());
+    }
+
+    #[test]
+    fn test_reduce_middleware() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+        store.add_middleware(TestReduceMiddleware {
+            new_action: TestAction::Decrement,
+        });
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(-1, store.state().counter);
+    }
+
+    #[test]
+    fn test_reduce_middleware_resumes_chain() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+        let count = Arc::new(AtomicUsize::new(0));
+        store.add_middleware(TestDuplicateMiddleware);
+        store.add_middleware(TestCountMiddleware {
+            count: count.clone(),
+        });
+
+        store.dispatch(TestAction::Increment);
+        assert_eq!(2, store.state().counter);
+        assert_eq!(2, count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_effect_middleware() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+        store.add_middleware(TestEffectMiddleware);
+
+        store.dispatch(TestAction::Decrent2Then1);
+        assert_eq!(-3, store.state().counter);
+    }
+
+    #[test]
+    fn test_dispatch_from_threads() {
+        let store = StoreRef::new(TestReducer, TestState { counter: 0 });
+        store.add_middleware(TestEffectMiddleware);
+
+        let callback_test = Arc::new(Mutex::new(0));
+        let callback_test_copy = callback_test.clone();
+        let callback: Callback<TestState, TestEvent> = Callback::new(move |_, _| {
+            *callback_test_copy.lock().unwrap() += 1;
+        });
+        store.subscribe_event(&callback, TestEvent::CounterChanged);
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        store.dispatch(TestAction::Increment);
+                        // chains a further decrement via the effect middleware
+                        store.dispatch(TestAction::Decrent2Then1);
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(-1600, store.state().counter);
+        assert_eq!(2400, *callback_test.lock().unwrap());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{ReduceError, Reducer};
+use std::{error::Error, fmt::Display, rc::Rc};
+
+/// A group of `Action`s being reduced against a working state within
+/// [Store::transaction()](crate::Store::transaction()).
+///
+/// The store's state is not modified until the transaction is
+/// committed, and the events and effects produced by the actions are
+/// held until then.
+pub struct Transaction<'a, State, Action, Event, Effect> {
+    reducer: &'a dyn Reducer<State, Action, Event, Effect>,
+    commit: TransactionCommit<State, Action, Event, Effect>,
+    rejected: Option<ReduceError>,
+}
+
+/// The actions reduced by a [Transaction], and the results of
+/// reducing them against the state which the transaction started
+/// from, to be committed by the store.
+pub(crate) struct TransactionCommit<State, Action, Event, Effect> {
+    /// The store's state when the transaction started.
+    pub base: Rc<State>,
+    /// The actions which were reduced, in order.
+    pub actions: Vec<Action>,
+    /// The working state produced by the actions.
+    pub state: Rc<State>,
+    pub events: Vec<Event>,
+    pub effects: Vec<Effect>,
+}
+
+impl<'a, State, Action, Event, Effect> Transaction<'a, State, Action, Event, Effect> {
+    pub(crate) fn new(
+        reducer: &'a dyn Reducer<State, Action, Event, Effect>,
+        state: Rc<State>,
+    ) -> Self {
+        Self {
+            reducer,
+            commit: TransactionCommit {
+                base: state.clone(),
+                actions: Vec::new(),
+                state,
+                events: Vec::new(),
+                effects: Vec::new(),
+            },
+            rejected: None,
+        }
+    }
+
+    /// Reduce an `Action` against the working state of this
+    /// transaction using [Reducer::try_reduce()]. Returns `false` if
+    /// the reducer rejected the action, or an earlier action in this
+    /// transaction was rejected, in which case the transaction will
+    /// be rolled back and any further actions are ignored.
+    pub fn dispatch<A: Into<Action>>(&mut self, action: A) -> bool {
+        if self.rejected.is_some() {
+            return false;
+        }
+
+        let action = action.into();
+        match self.reducer.try_reduce(&self.commit.state, &action) {
+            Ok(result) => {
+                self.commit.actions.push(action);
+                self.commit.state = result.state;
+                self.commit.events.extend(result.events);
+                self.commit.effects.extend(result.effects);
+                true
+            }
+            Err(error) => {
+                self.rejected = Some(error);
+                false
+            }
+        }
+    }
+
+    /// The working state of this transaction, produced by the actions
+    /// which have been dispatched to it so far.
+    pub fn state(&self) -> Rc<State> {
+        self.commit.state.clone()
+    }
+
+    /// Returns `true` if an action dispatched to this transaction was
+    /// rejected by the reducer.
+    pub fn is_rejected(&self) -> bool {
+        self.rejected.is_some()
+    }
+
+    /// The [TransactionCommit] to be committed by the store, or the
+    /// [ReduceError] if an action was rejected.
+    pub(crate) fn finish(
+        self,
+    ) -> Result<TransactionCommit<State, Action, Event, Effect>, ReduceError> {
+        match self.rejected {
+            Some(error) => Err(error),
+            None => Ok(self.commit),
+        }
+    }
+}
+
+/// The reason that a
+/// [Store::transaction()](crate::Store::transaction()) was rolled
+/// back.
+#[derive(Debug)]
+pub enum TransactionError<E> {
+    /// The transaction closure returned an error.
+    Aborted(E),
+    /// An action dispatched within the transaction was rejected by
+    /// [Reducer::try_reduce()].
+    Rejected(ReduceError),
+}
+
+impl<E: Display> Display for TransactionError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionError::Aborted(error) => write!(f, "transaction aborted: {}", error),
+            TransactionError::Rejected(error) => write!(f, "transaction rolled back: {}", error),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for TransactionError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TransactionError::Aborted(error) => Some(error),
+            TransactionError::Rejected(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,804 @@
+//! A [Reducer] wrapper which adds undo/redo support to any other
+//! [Reducer], see [UndoableReducer].
+//!
+//! ## Example
+//!
+//! ```
+//! use reactive_state::{
+//!     undo::{History, UndoableAction, UndoableReducer},
+//!     ReducerFn, ReducerResult, Store,
+//! };
+//! use std::rc::Rc;
+//!
+//! let reducer: ReducerFn<i32, i32, (), ()> = |state, action| ReducerResult {
+//!     state: Rc::new(**state + *action),
+//!     events: vec![()],
+//!     effects: vec![],
+//! };
+//!
+//! let store = Store::new(UndoableReducer::new(reducer).limit(100), History::new(0));
+//!
+//! store.dispatch(1);
+//! store.dispatch(2);
+//! assert_eq!(3, **store.state());
+//!
+//! store.dispatch(UndoableAction::Undo);
+//! assert_eq!(1, **store.state());
+//!
+//! store.dispatch(UndoableAction::Redo);
+//! assert_eq!(3, **store.state());
+//! ```
+
+use crate::{ReduceError, Reducer, ReducerResult};
+use std::{fmt, ops::Deref, rc::Rc};
+
+/// The `Action` type for a [Store](crate::Store) using an
+/// [UndoableReducer].
+#[derive(Debug, Clone, PartialEq)]
+pub enum UndoableAction<Action> {
+    /// An action to be reduced by the wrapped [Reducer], and recorded
+    /// in the [History].
+    Action(Action),
+    /// Return to the previous state in the [History].
+    Undo,
+    /// Return to the next state in the [History], reversing an
+    /// [UndoableAction::Undo].
+    Redo,
+    /// Clear all the past and future states from the [History],
+    /// keeping the present state.
+    ClearHistory,
+}
+
+impl<Action> From<Action> for UndoableAction<Action> {
+    fn from(action: Action) -> Self {
+        UndoableAction::Action(action)
+    }
+}
+
+/// The `Event` type for a [Store](crate::Store) using an
+/// [UndoableReducer].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UndoableEvent<Event> {
+    /// An event produced by the wrapped [Reducer].
+    Event(Event),
+    /// The present state was replaced by a past state.
+    Undo,
+    /// The present state was replaced by a future state.
+    Redo,
+    /// The past and future states were cleared.
+    ClearHistory,
+}
+
+/// A node of a [StateList].
+struct StateNode<State> {
+    state: Rc<State>,
+    next: Option<Rc<StateNode<State>>>,
+    /// The number of nodes from this one to the end of the list.
+    depth: usize,
+}
+
+impl<State> Drop for StateNode<State> {
+    fn drop(&mut self) {
+        // Drop the nodes which aren't shared with another list in a
+        // loop, rather than recursively, so that dropping a long
+        // history doesn't overflow the stack.
+        let mut next = self.next.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+/// An immutable list of states, most recent first, which shares its
+/// nodes with the lists it was derived from. Pushing or popping a
+/// state doesn't copy the rest of the list, so a [History] can be
+/// cloned in constant time.
+///
+/// Only the first `len` nodes belong to the list, so that it can be
+/// truncated without copying; the nodes past the end are dropped
+/// when the list is rebuilt in [StateList::truncate()].
+struct StateList<State> {
+    head: Option<Rc<StateNode<State>>>,
+    len: usize,
+}
+
+impl<State> StateList<State> {
+    fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn push(&mut self, state: Rc<State>) {
+        let next = self.head.take();
+        let depth = next.as_ref().map_or(0, |node| node.depth) + 1;
+        self.head = Some(Rc::new(StateNode { state, next, depth }));
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<Rc<State>> {
+        let node = self.head.take()?;
+        self.len -= 1;
+        if self.len > 0 {
+            self.head = node.next.clone();
+        }
+        Some(node.state.clone())
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Keep at most the `limit` most recent states. The list is only
+    /// rebuilt once it holds twice as many nodes as the `limit`, so
+    /// truncating it after every push costs amortized constant time.
+    fn truncate(&mut self, limit: usize) {
+        if self.len <= limit {
+            return;
+        }
+        self.len = limit;
+        if limit == 0 {
+            self.head = None;
+        } else if self.head.as_ref().map_or(0, |node| node.depth) > 2 * limit {
+            let states: Vec<Rc<State>> = self.iter().cloned().collect();
+            *self = Self::new();
+            for state in states.into_iter().rev() {
+                self.push(state);
+            }
+        }
+    }
+
+    /// The states, from the most recent to the oldest.
+    fn iter(&self) -> impl Iterator<Item = &Rc<State>> {
+        let mut node = self.head.as_deref();
+        std::iter::from_fn(move || {
+            let current = node?;
+            node = current.next.as_deref();
+            Some(&current.state)
+        })
+        .take(self.len)
+    }
+}
+
+impl<State> Clone for StateList<State> {
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<State: fmt::Debug> fmt::Debug for StateList<State> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// The `State` type for a [Store](crate::Store) using an
+/// [UndoableReducer], containing the present state produced by the
+/// wrapped [Reducer], and the past/future states which can be
+/// returned to.
+///
+/// This dereferences to the present state.
+#[derive(Debug)]
+pub struct History<State> {
+    past: StateList<State>,
+    present: Rc<State>,
+    future: StateList<State>,
+    /// The group of the action which produced the present state, see
+    /// [UndoableReducer::group_by()].
+    group: Option<u64>,
+}
+
+impl<State> History<State> {
+    /// Create a new [History] with the specified `present` state, and
+    /// no past or future states.
+    pub fn new(present: State) -> Self {
+        Self {
+            past: StateList::new(),
+            present: Rc::new(present),
+            future: StateList::new(),
+            group: None,
+        }
+    }
+
+    /// The present state.
+    pub fn present(&self) -> &Rc<State> {
+        &self.present
+    }
+
+    /// The past states, from oldest to most recent.
+    pub fn past(&self) -> impl Iterator<Item = &Rc<State>> {
+        let past: Vec<&Rc<State>> = self.past.iter().collect();
+        past.into_iter().rev()
+    }
+
+    /// The future states (which have been undone), from the next
+    /// state to be redone to the last.
+    pub fn future(&self) -> impl Iterator<Item = &Rc<State>> {
+        self.future.iter()
+    }
+
+    /// Returns `true` if there is a past state to return to using
+    /// [UndoableAction::Undo].
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+
+    /// Returns `true` if there is a future state to return to using
+    /// [UndoableAction::Redo].
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+}
+
+impl<State> Clone for History<State> {
+    fn clone(&self) -> Self {
+        Self {
+            past: self.past.clone(),
+            present: self.present.clone(),
+            future: self.future.clone(),
+            group: self.group,
+        }
+    }
+}
+
+impl<State> Deref for History<State> {
+    type Target = State;
+
+    fn deref(&self) -> &Self::Target {
+        &self.present
+    }
+}
+
+impl<State> Default for History<State>
+where
+    State: Default,
+{
+    fn default() -> Self {
+        Self::new(State::default())
+    }
+}
+
+/// See [UndoableReducer::filter()].
+type FilterFn<Action> = dyn Fn(&Action) -> bool;
+
+/// See [UndoableReducer::group_by()].
+type GroupByFn<Action> = dyn Fn(&Action) -> Option<u64>;
+
+/// A [Reducer] which wraps another [Reducer], and keeps a bounded
+/// [History] of the past and future states that it has produced,
+/// which can be returned to using the [UndoableAction::Undo] and
+/// [UndoableAction::Redo] actions.
+///
+/// Because states are immutable and kept behind an [Rc], storing
+/// the history only costs a pointer per state, and the past and future
+/// states are shared between successive [History] values, so
+/// reducing an action doesn't copy them.
+///
+/// See [undo](crate::undo) for an example.
+pub struct UndoableReducer<R, Action> {
+    reducer: R,
+    limit: Option<usize>,
+    filter: Option<Box<FilterFn<Action>>>,
+    group_by: Option<Box<GroupByFn<Action>>>,
+}
+
+impl<R, Action> UndoableReducer<R, Action> {
+    /// Create a new [UndoableReducer] wrapping the specified
+    /// `reducer`, with an unbounded history.
+    pub fn new(reducer: R) -> Self {
+        Self {
+            reducer,
+            limit: None,
+            filter: None,
+            group_by: None,
+        }
+    }
+
+    /// Limit the number of past states kept in the history. When the
+    /// limit is reached, the oldest state is discarded.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Only record the states produced by actions for which `filter`
+    /// returns `true` in the history. The state produced by other
+    /// actions replaces the present state without creating a new
+    /// undo step (the future states are still discarded, and the
+    /// current group is ended).
+    pub fn filter<F: Fn(&Action) -> bool + 'static>(mut self, filter: F) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Group consecutive actions into a single undo step. The
+    /// `group_by` function returns the group for an action (or `None`
+    /// if it doesn't belong to a group), and consecutive actions
+    /// belonging to the same group are undone together.
+    pub fn group_by<F: Fn(&Action) -> Option<u64> + 'static>(mut self, group_by: F) -> Self {
+        self.group_by = Some(Box::new(group_by));
+        self
+    }
+
+    /// Record the `result` of the wrapped reducer reducing the
+    /// `action` against the present state in the history.
+    fn record<State, Event, Effect>(
+        &self,
+        prev_state: &Rc<History<State>>,
+        action: &Action,
+        result: ReducerResult<State, Event, Effect>,
+    ) -> ReducerResult<History<State>, UndoableEvent<Event>, Effect> {
+        let events = result
+            .events
+            .into_iter()
+            .map(UndoableEvent::Event)
+            .collect();
+
+        if Rc::ptr_eq(&result.state, &prev_state.present) {
+            return ReducerResult {
+                state: prev_state.clone(),
+                events,
+                effects: result.effects,
+            };
+        }
+
+        let mut history = History::clone(prev_state);
+        let present = std::mem::replace(&mut history.present, result.state);
+
+        let record = match &self.filter {
+            Some(filter) => filter(action),
+            None => true,
+        };
+
+        if record {
+            let group = self.group_by.as_ref().and_then(|group_by| group_by(action));
+            if group.is_none() || group != history.group {
+                history.past.push(present);
+                if let Some(limit) = self.limit {
+                    history.past.truncate(limit);
+                }
+            }
+            history.group = group;
+        } else {
+            history.group = None;
+        }
+        // The future states were undone from a different present
+        // state, so they can no longer be returned to.
+        history.future.clear();
+
+        ReducerResult {
+            state: Rc::new(history),
+            events,
+            effects: result.effects,
+        }
+    }
+}
+
+impl<R, State, Action, Event, Effect>
+    Reducer<History<State>, UndoableAction<Action>, UndoableEvent<Event>, Effect>
+    for UndoableReducer<R, Action>
+where
+    R: Reducer<State, Action, Event, Effect>,
+{
+    fn reduce(
+        &self,
+        prev_state: &Rc<History<State>>,
+        action: &UndoableAction<Action>,
+    ) -> ReducerResult<History<State>, UndoableEvent<Event>, Effect> {
+        let unchanged = || ReducerResult {
+            state: prev_state.clone(),
+            events: Vec::new(),
+            effects: Vec::new(),
+        };
+
+        let (history, event) = match action {
+            UndoableAction::Action(action) => {
+                let result = self.reducer.reduce(&prev_state.present, action);
+                return self.record(prev_state, action, result);
+            }
+            UndoableAction::Undo => {
+                if !prev_state.can_undo() {
+                    return unchanged();
+                }
+                let mut history = History::clone(prev_state);
+                let present = history.past.pop().expect("past should not be empty");
+                history
+                    .future
+                    .push(std::mem::replace(&mut history.present, present));
+                history.group = None;
+                (history, UndoableEvent::Undo)
+            }
+            UndoableAction::Redo => {
+                if !prev_state.can_redo() {
+                    return unchanged();
+                }
+                let mut history = History::clone(prev_state);
+                let present = history.future.pop().expect("future should not be empty");
+                history
+                    .past
+                    .push(std::mem::replace(&mut history.present, present));
+                history.group = None;
+                (history, UndoableEvent::Redo)
+            }
+            UndoableAction::ClearHistory => {
+                if !prev_state.can_undo() && !prev_state.can_redo() {
+                    return unchanged();
+                }
+                let mut history = History::clone(prev_state);
+                history.past.clear();
+                history.future.clear();
+                history.group = None;
+                (history, UndoableEvent::ClearHistory)
+            }
+        };
+
+        ReducerResult {
+            state: Rc::new(history),
+            events: vec![event],
+            effects: Vec::new(),
+        }
+    }
+
+    fn try_reduce(
+        &self,
+        prev_state: &Rc<History<State>>,
+        action: &UndoableAction<Action>,
+    ) -> Result<ReducerResult<History<State>, UndoableEvent<Event>, Effect>, ReduceError> {
+        match action {
+            UndoableAction::Action(action) => {
+                let result = self.reducer.try_reduce(&prev_state.present, action)?;
+                Ok(self.record(prev_state, action, result))
+            }
+            _ => Ok(self.reduce(prev_state, action)),
+        }
+    }
+
+    fn is_fallible(&self) -> bool {
+        self.reducer.is_fallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, UndoableAction, UndoableEvent, UndoableReducer};
+    use crate::{ReduceError, Reducer, ReducerFn, ReducerResult, Store, TransactionError};
+    use std::rc::Rc;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum TestAction {
+        Add(i32),
+        Type(char),
+        Nothing,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestState {
+        counter: i32,
+        text: String,
+    }
+
+    fn test_reducer() -> ReducerFn<TestState, TestAction, TestAction, ()> {
+        |state, action| {
+            let mut new_state = TestState::clone(state);
+            match action {
+                TestAction::Add(value) => new_state.counter += value,
+                TestAction::Type(c) => new_state.text.push(*c),
+                TestAction::Nothing => {
+                    return ReducerResult {
+                        state: state.clone(),
+                        events: vec![],
+                        effects: vec![],
+                    }
+                }
+            }
+            ReducerResult {
+                state: Rc::new(new_state),
+                events: vec![action.clone()],
+                effects: vec![],
+            }
+        }
+    }
+
+    fn initial_state() -> Rc<History<TestState>> {
+        Rc::new(History::new(TestState {
+            counter: 0,
+            text: String::new(),
+        }))
+    }
+
+    fn reduce_all<R>(
+        reducer: &R,
+        state: Rc<History<TestState>>,
+        actions: Vec<UndoableAction<TestAction>>,
+    ) -> Rc<History<TestState>>
+    where
+        R: Reducer<History<TestState>, UndoableAction<TestAction>, UndoableEvent<TestAction>, ()>,
+    {
+        actions
+            .into_iter()
+            .fold(state, |state, action| reducer.reduce(&state, &action).state)
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let reducer = UndoableReducer::new(test_reducer());
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            vec![TestAction::Add(1).into(), TestAction::Add(2).into()],
+        );
+        assert_eq!(3, state.counter);
+        assert_eq!(2, state.past().count());
+
+        let result = reducer.reduce(&state, &UndoableAction::Undo);
+        assert_eq!(1, result.state.counter);
+        assert_eq!(vec![UndoableEvent::Undo], result.events);
+        assert!(result.state.can_redo());
+
+        let result = reducer.reduce(&result.state, &UndoableAction::Redo);
+        assert_eq!(3, result.state.counter);
+        assert_eq!(vec![UndoableEvent::Redo], result.events);
+        assert!(!result.state.can_redo());
+
+        let state = reduce_all(
+            &reducer,
+            result.state,
+            vec![
+                UndoableAction::Undo,
+                UndoableAction::Undo,
+                UndoableAction::Undo,
+            ],
+        );
+        assert_eq!(0, state.counter);
+        assert!(!state.can_undo());
+        assert_eq!(2, state.future().count());
+
+        // A new action discards the future.
+        let state = reduce_all(&reducer, state, vec![TestAction::Add(5).into()]);
+        assert_eq!(5, state.counter);
+        assert!(!state.can_redo());
+    }
+
+    #[test]
+    fn test_unchanged_not_recorded() {
+        let reducer = UndoableReducer::new(test_reducer());
+        let state = initial_state();
+        let result = reducer.reduce(&state, &TestAction::Nothing.into());
+        assert!(Rc::ptr_eq(&state, &result.state));
+
+        let result = reducer.reduce(&state, &UndoableAction::Undo);
+        assert!(Rc::ptr_eq(&state, &result.state));
+        assert!(result.events.is_empty());
+    }
+
+    #[test]
+    fn test_limit() {
+        let reducer = UndoableReducer::new(test_reducer()).limit(2);
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            (1..=4).map(|i| TestAction::Add(i).into()).collect(),
+        );
+        assert_eq!(10, state.counter);
+        assert_eq!(
+            vec![3, 6],
+            state.past().map(|s| s.counter).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_limit_many_actions() {
+        let reducer = UndoableReducer::new(test_reducer()).limit(3);
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            (1..=20).map(|_| TestAction::Add(1).into()).collect(),
+        );
+        assert_eq!(
+            vec![17, 18, 19],
+            state.past().map(|s| s.counter).collect::<Vec<_>>()
+        );
+
+        let state = reduce_all(&reducer, state, vec![UndoableAction::Undo; 4]);
+        assert_eq!(17, state.counter);
+        assert_eq!(
+            vec![18, 19, 20],
+            state.future().map(|s| s.counter).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_history_shared() {
+        let reducer = UndoableReducer::new(test_reducer());
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            (1..=100_000).map(|_| TestAction::Add(1).into()).collect(),
+        );
+        assert_eq!(100_000, state.past().count());
+
+        // Reducing from a state leaves its history untouched.
+        let undone = reduce_all(&reducer, state.clone(), vec![UndoableAction::Undo; 2]);
+        assert_eq!(99_998, undone.counter);
+        assert_eq!(100_000, state.past().count());
+        assert!(!state.can_redo());
+        assert_eq!(
+            vec![99_999, 100_000],
+            undone.future().map(|s| s.counter).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let reducer = UndoableReducer::new(test_reducer())
+            .filter(|action| matches!(action, TestAction::Add(_)));
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            vec![
+                TestAction::Add(1).into(),
+                TestAction::Type('a').into(),
+                TestAction::Type('b').into(),
+            ],
+        );
+        assert_eq!("ab", state.text);
+        assert_eq!(1, state.past().count());
+
+        let state = reduce_all(&reducer, state, vec![UndoableAction::Undo]);
+        assert_eq!(0, state.counter);
+        assert!(!state.can_undo());
+    }
+
+    #[test]
+    fn test_filter_clears_future() {
+        let reducer = UndoableReducer::new(test_reducer())
+            .filter(|action| matches!(action, TestAction::Add(_)));
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            vec![
+                TestAction::Add(1).into(),
+                UndoableAction::Undo,
+                TestAction::Type('a').into(),
+            ],
+        );
+        assert_eq!("a", state.text);
+        assert_eq!(0, state.counter);
+        assert!(!state.can_redo());
+
+        // Redo doesn't bring back the stale state without the text.
+        let state = reduce_all(&reducer, state, vec![UndoableAction::Redo]);
+        assert_eq!("a", state.text);
+    }
+
+    #[test]
+    fn test_filter_resets_group() {
+        let reducer = UndoableReducer::new(test_reducer())
+            .filter(|action| matches!(action, TestAction::Add(_)))
+            .group_by(|_| Some(1));
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            vec![
+                TestAction::Add(1).into(),
+                TestAction::Type('a').into(),
+                TestAction::Add(2).into(),
+            ],
+        );
+        assert_eq!(3, state.counter);
+        assert_eq!(2, state.past().count());
+
+        let state = reduce_all(&reducer, state, vec![UndoableAction::Undo]);
+        assert_eq!(1, state.counter);
+        assert_eq!("a", state.text);
+    }
+
+    #[test]
+    fn test_group_by() {
+        let reducer = UndoableReducer::new(test_reducer()).group_by(|action| match action {
+            TestAction::Type(_) => Some(1),
+            _ => None,
+        });
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            vec![
+                TestAction::Type('a').into(),
+                TestAction::Add(1).into(),
+                TestAction::Type('b').into(),
+                TestAction::Type('c').into(),
+            ],
+        );
+        assert_eq!("abc", state.text);
+
+        let state = reduce_all(&reducer, state, vec![UndoableAction::Undo]);
+        assert_eq!("a", state.text);
+        assert_eq!(1, state.counter);
+
+        let state = reduce_all(&reducer, state, vec![UndoableAction::Undo]);
+        assert_eq!("a", state.text);
+        assert_eq!(0, state.counter);
+    }
+
+    /// Rejects actions which would make the counter negative.
+    struct PositiveReducer;
+
+    impl Reducer<TestState, TestAction, TestAction, ()> for PositiveReducer {
+        fn reduce(
+            &self,
+            state: &Rc<TestState>,
+            action: &TestAction,
+        ) -> ReducerResult<TestState, TestAction, ()> {
+            test_reducer()(state, action)
+        }
+
+        fn try_reduce(
+            &self,
+            state: &Rc<TestState>,
+            action: &TestAction,
+        ) -> Result<ReducerResult<TestState, TestAction, ()>, ReduceError> {
+            let result = self.reduce(state, action);
+            if result.state.counter < 0 {
+                return Err(ReduceError::new("negative counter"));
+            }
+            Ok(result)
+        }
+    }
+
+    #[test]
+    fn test_try_reduce() {
+        let reducer = UndoableReducer::new(PositiveReducer);
+        let state = initial_state();
+        assert!(reducer
+            .try_reduce(&state, &TestAction::Add(-1).into())
+            .is_err());
+        let result = reducer
+            .try_reduce(&state, &TestAction::Add(1).into())
+            .unwrap();
+        assert_eq!(1, result.state.counter);
+        assert!(result.state.can_undo());
+
+        let store = Store::new(reducer, History::clone(&initial_state()));
+        let result: Result<(), TransactionError<()>> = store.transaction(|tx| {
+            tx.dispatch(TestAction::Add(1));
+            tx.dispatch(TestAction::Add(-2));
+            Ok(())
+        });
+        assert!(matches!(result, Err(TransactionError::Rejected(_))));
+        assert_eq!(0, store.state().counter);
+        assert!(!store.state().can_undo());
+    }
+
+    #[test]
+    fn test_clear_history() {
+        let reducer = UndoableReducer::new(test_reducer());
+        let state = reduce_all(
+            &reducer,
+            initial_state(),
+            vec![
+                TestAction::Add(1).into(),
+                TestAction::Add(1).into(),
+                UndoableAction::Undo,
+            ],
+        );
+        let result = reducer.reduce(&state, &UndoableAction::ClearHistory);
+        assert_eq!(vec![UndoableEvent::ClearHistory], result.events);
+        assert_eq!(1, result.state.counter);
+        assert!(!result.state.can_undo());
+        assert!(!result.state.can_redo());
+
+        // Clearing an empty history doesn't change the state.
+        let state = result.state;
+        let result = reducer.reduce(&state, &UndoableAction::ClearHistory);
+        assert!(Rc::ptr_eq(&state, &result.state));
+        assert!(result.events.is_empty());
+    }
+}